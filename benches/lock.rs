@@ -0,0 +1,97 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Acquisition benchmarks against std and parking_lot.
+
+Run with `cargo bench`.  Each group compares the same operation across implementations.
+*/
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+use atomiclock::AtomicLock;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn uncontended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("uncontended lock/unlock");
+    let atomic = AtomicLock::new(0u64);
+    group.bench_function("AtomicLock", |b| b.iter(|| {
+        *black_box(&atomic).lock().unwrap() += 1;
+    }));
+    let std = std::sync::Mutex::new(0u64);
+    group.bench_function("std::sync::Mutex", |b| b.iter(|| {
+        *black_box(&std).try_lock().unwrap() += 1;
+    }));
+    let parking_lot = parking_lot::Mutex::new(0u64);
+    group.bench_function("parking_lot::Mutex", |b| b.iter(|| {
+        *black_box(&parking_lot).try_lock().unwrap() += 1;
+    }));
+    group.finish();
+}
+
+fn busy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("failed try_lock");
+    let atomic = AtomicLock::new(0u64);
+    let _atomic_guard = atomic.lock().unwrap();
+    group.bench_function("AtomicLock", |b| b.iter(|| {
+        assert!(black_box(&atomic).lock().is_none());
+    }));
+    let std = std::sync::Mutex::new(0u64);
+    let _std_guard = std.lock().unwrap();
+    group.bench_function("std::sync::Mutex", |b| b.iter(|| {
+        assert!(black_box(&std).try_lock().is_err());
+    }));
+    let parking_lot = parking_lot::Mutex::new(0u64);
+    let _parking_lot_guard = parking_lot.lock();
+    group.bench_function("parking_lot::Mutex", |b| b.iter(|| {
+        assert!(black_box(&parking_lot).try_lock().is_none());
+    }));
+    group.finish();
+}
+
+const THREADS: usize = 4;
+const OPS: usize = 10_000;
+
+//every thread spins on try_lock until it has done OPS increments
+fn contended_run<L: Send + Sync + 'static>(lock: Arc<L>, op: fn(&L) -> bool) {
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let threads: Vec<_> = (0..THREADS).map(|_| {
+        let lock = lock.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            barrier.wait();
+            let mut done = 0;
+            while done < OPS {
+                if op(&lock) {
+                    done += 1;
+                } else {
+                    std::hint::spin_loop();
+                }
+            }
+        })
+    }).collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+fn contended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended try_lock");
+    group.sample_size(20);
+    let atomic = Arc::new(AtomicLock::new(0u64));
+    group.bench_function("AtomicLock", |b| b.iter(|| contended_run(atomic.clone(), |l| match l.lock() {
+        Some(mut guard) => { *guard += 1; true }
+        None => false,
+    })));
+    let std = Arc::new(std::sync::Mutex::new(0u64));
+    group.bench_function("std::sync::Mutex", |b| b.iter(|| contended_run(std.clone(), |l| match l.try_lock() {
+        Ok(mut guard) => { *guard += 1; true }
+        Err(_) => false,
+    })));
+    let parking_lot = Arc::new(parking_lot::Mutex::new(0u64));
+    group.bench_function("parking_lot::Mutex", |b| b.iter(|| contended_run(parking_lot.clone(), |l| match l.try_lock() {
+        Some(mut guard) => { *guard += 1; true }
+        None => false,
+    })));
+    group.finish();
+}
+
+criterion_group!(benches, uncontended, busy, contended);
+criterion_main!(benches);
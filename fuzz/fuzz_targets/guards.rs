@@ -0,0 +1,48 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Runs a sequence of lock, write, drop and pop operations from the input, checking each against a model. */
+#![no_main]
+
+use std::collections::VecDeque;
+use atomiclock::{AtomicLock, Guard, LockedQueue};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let locks = [AtomicLock::new(0u8), AtomicLock::new(0u8)];
+    let mut guards: [Option<Guard<'_, u8>>; 2] = [None, None];
+    let mut values = [0u8; 2];
+    let queue: LockedQueue<u8, 4> = LockedQueue::new();
+    let mut queued = VecDeque::new();
+    for &op in data {
+        let i = (op & 1) as usize;
+        match (op >> 1) % 6 {
+            0 => {
+                let guard = locks[i].lock();
+                assert_eq!(guard.is_some(), guards[i].is_none());
+                if guard.is_some() {
+                    guards[i] = guard;
+                }
+            }
+            1 => guards[i] = None,
+            2 => {
+                if let Some(guard) = &mut guards[i] {
+                    **guard = guard.wrapping_add(op);
+                    values[i] = values[i].wrapping_add(op);
+                }
+            }
+            3 => assert_eq!(locks[i].is_locked(), guards[i].is_some()),
+            4 => {
+                let pushed = queue.try_push(op);
+                assert_eq!(pushed.is_ok(), queued.len() < 4);
+                if pushed.is_ok() {
+                    queued.push_back(op);
+                }
+            }
+            _ => assert_eq!(queue.try_pop(), queued.pop_front()),
+        }
+    }
+    drop(guards);
+    for (lock, value) in locks.iter().zip(values) {
+        assert_eq!(*lock.lock().unwrap(), value);
+    }
+    assert_eq!(queue.len(), queued.len());
+});
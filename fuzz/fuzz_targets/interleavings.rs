@@ -0,0 +1,56 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Races threads over a lock and a queue, with the input seeding the yield points that steer them. */
+#![no_main]
+
+use atomiclock::{seeded_yield, set_yield_hook, set_yield_seed, AtomicLock, LockedQueue};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((seed, rest)) = data.split_first_chunk::<8>() else { return };
+    set_yield_seed(u64::from_le_bytes(*seed));
+    set_yield_hook(Some(seeded_yield));
+    let rounds = rest.first().map_or(1, |&b| u32::from(b % 32) + 1);
+
+    let lock = AtomicLock::new(0u32);
+    let queue: LockedQueue<u32, 2> = LockedQueue::new();
+    let popped = std::thread::scope(|s| {
+        for _ in 0..2 {
+            s.spawn(|| {
+                for _ in 0..rounds {
+                    loop {
+                        if let Some(mut guard) = lock.lock() {
+                            *guard += 1;
+                            break;
+                        }
+                        std::thread::yield_now();
+                    }
+                }
+            });
+        }
+        s.spawn(|| {
+            for i in 0..rounds {
+                let mut value = i;
+                while let Err(back) = queue.try_push(value) {
+                    value = back;
+                    std::thread::yield_now();
+                }
+            }
+        });
+        let consumer = s.spawn(|| {
+            let mut popped = Vec::new();
+            while popped.len() < rounds as usize {
+                match queue.try_pop() {
+                    Some(value) => popped.push(value),
+                    None => std::thread::yield_now(),
+                }
+            }
+            popped
+        });
+        consumer.join().unwrap()
+    });
+    set_yield_hook(None);
+
+    assert_eq!(*lock.lock().unwrap(), 2 * rounds);
+    //one producer and one consumer, so the queue keeps order
+    assert!(popped.iter().copied().eq(0..rounds));
+});
@@ -0,0 +1,186 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Wrappers that give any [TryLock] a policy for when it's busy. */
+
+use crate::{Clock, TryLock};
+
+/**
+Makes any [TryLock] a spinlock.
+
+[Spin::lock] busy-waits until the lock is available.  Good for short critical sections,
+or where there's nothing else to do.
+
+Between attempts, it spins `BACKOFF` times, 1 by default.  Contended locks can do better with
+more, since each attempt takes the lock's cache line away from the holder.  It's a constant,
+so the compiler can unroll the loop; pick it with [Spin::with_backoff].
+*/
+#[derive(Debug, Default)]
+pub struct Spin<L, const BACKOFF: u32 = 1> {
+    lock: L,
+}
+
+impl<L> Spin<L> {
+    /**
+    Wraps `lock`.
+    */
+    pub const fn new(lock: L) -> Self {
+        Spin { lock }
+    }
+}
+
+impl<L, const BACKOFF: u32> Spin<L, BACKOFF> {
+    /**
+    Wraps `lock`, spinning `BACKOFF` times between attempts, e.g. `Spin::<_, 16>::with_backoff(lock)`.
+    */
+    pub const fn with_backoff(lock: L) -> Self {
+        Spin { lock }
+    }
+
+    /**
+    Consumes the wrapper, returning the inner lock.
+    */
+    pub fn into_inner(self) -> L {
+        self.lock
+    }
+}
+
+impl<L: TryLock, const BACKOFF: u32> Spin<L, BACKOFF> {
+    /**
+    Spins until the lock is available.
+    */
+    pub fn lock(&self) -> L::Guard<'_> {
+        loop {
+            if let Some(guard) = self.lock.try_lock() {
+                return guard;
+            }
+            for _ in 0..BACKOFF {
+                core::hint::spin_loop();
+            }
+        }
+    }
+}
+
+/**
+Makes any [TryLock] yield the thread while it's busy.
+
+[Yielding::lock] calls [std::thread::yield_now] between attempts, so other threads,
+including perhaps the holder, get to run.
+*/
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct Yielding<L> {
+    lock: L,
+}
+
+#[cfg(feature = "std")]
+impl<L> Yielding<L> {
+    /**
+    Wraps `lock`.
+    */
+    pub const fn new(lock: L) -> Self {
+        Yielding { lock }
+    }
+
+    /**
+    Consumes the wrapper, returning the inner lock.
+    */
+    pub fn into_inner(self) -> L {
+        self.lock
+    }
+}
+
+#[cfg(feature = "std")]
+impl<L: TryLock> Yielding<L> {
+    /**
+    Yields until the lock is available.
+    */
+    pub fn lock(&self) -> L::Guard<'_> {
+        loop {
+            if let Some(guard) = self.lock.try_lock() {
+                return guard;
+            }
+            std::thread::yield_now();
+        }
+    }
+}
+
+/**
+Makes any [TryLock] spin for up to a fixed number of ticks of a [Clock].
+
+[Timeout::lock] gives up and returns None once the timeout has passed.
+*/
+#[derive(Debug)]
+pub struct Timeout<L, C> {
+    lock: L,
+    clock: C,
+    timeout: u64,
+}
+
+impl<L, C> Timeout<L, C> {
+    /**
+    Wraps `lock`, giving up after `timeout` ticks of `clock`.
+    */
+    pub const fn new(lock: L, clock: C, timeout: u64) -> Self {
+        Timeout { lock, clock, timeout }
+    }
+
+    /**
+    The timeout, in ticks.
+    */
+    pub fn timeout(&self) -> u64 {
+        self.timeout
+    }
+
+    /**
+    Consumes the wrapper, returning the inner lock.
+    */
+    pub fn into_inner(self) -> L {
+        self.lock
+    }
+}
+
+impl<L: TryLock, C: Clock> Timeout<L, C> {
+    /**
+    Spins until the lock is available, or the timeout has passed.
+
+    Tries at least once, so a timeout of 0 behaves like [TryLock::try_lock].
+    */
+    pub fn lock(&self) -> Option<L::Guard<'_>> {
+        let start = self.clock.now();
+        loop {
+            if let Some(guard) = self.lock.try_lock() {
+                return Some(guard);
+            }
+            if self.clock.now().wrapping_sub(start) >= self.timeout {
+                return None;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+//the wrappers are locks too, so they stack
+
+impl<L: TryLock, const BACKOFF: u32> TryLock for Spin<L, BACKOFF> {
+    type Target = L::Target;
+    type Guard<'a> = L::Guard<'a> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock.try_lock()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<L: TryLock> TryLock for Yielding<L> {
+    type Target = L::Target;
+    type Guard<'a> = L::Guard<'a> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock.try_lock()
+    }
+}
+
+impl<L: TryLock, C> TryLock for Timeout<L, C> {
+    type Target = L::Target;
+    type Guard<'a> = L::Guard<'a> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock.try_lock()
+    }
+}
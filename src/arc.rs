@@ -0,0 +1,113 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Snapshot reads of a value that writers replace wholesale. */
+
+use alloc::sync::Arc;
+use core::marker::PhantomData;
+use crate::{AtomicLock, Guard};
+
+/**
+A value that readers snapshot as an `Arc`, and writers replace with a new `Arc`, read-copy-update style.
+
+Readers hold the lock only long enough to clone the `Arc`, so they never wait for a writer to
+finish building the new value, and the snapshot stays valid however long they keep it.
+This suits read-mostly data, like configuration that's hot-reloaded.
+
+Writers are serialized among themselves by a second lock, so [ArcLock::try_update] never loses an update.
+*/
+pub struct ArcLock<T> {
+    current: AtomicLock<Arc<T>>,
+    writer: AtomicLock<()>,
+    //snapshots cross threads, so we're Send and Sync exactly when Arc<T> is
+    _marker: PhantomData<Arc<T>>,
+}
+
+impl<T> ArcLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub fn new(data: T) -> Self {
+        ArcLock::from(Arc::new(data))
+    }
+
+    /**
+    A snapshot of the current value.
+
+    Only waits for other threads that are cloning or swapping the `Arc` right now.
+    */
+    pub fn load(&self) -> Arc<T> {
+        self.current().clone()
+    }
+
+    /**
+    Installs `value` as the current value, returning the old one.
+
+    If another writer is busy, gives `value` back as the error.
+    */
+    pub fn try_store(&self, value: Arc<T>) -> Result<Arc<T>, Arc<T>> {
+        match self.writer.lock() {
+            Some(_writer) => Ok(core::mem::replace(&mut *self.current(), value)),
+            None => Err(value),
+        }
+    }
+
+    /**
+    Replaces the current value with `f` of it, returning the old one.
+
+    `f` runs without blocking readers.  If another writer is busy, returns None without calling `f`.
+    */
+    pub fn try_update<F>(&self, f: F) -> Option<Arc<T>> where F: FnOnce(&T) -> T {
+        let _writer = self.writer.lock()?;
+        //nobody else can store while we hold the writer lock, so this is still current when we swap
+        let old = self.load();
+        let new = Arc::new(f(&old));
+        *self.current() = new;
+        Some(old)
+    }
+
+    /**
+    Consumes the lock, returning the current value.
+    */
+    pub fn into_inner(self) -> Arc<T> {
+        self.current.into_inner()
+    }
+
+    //the slot is only held to clone or swap, so this spins briefly if at all
+    fn current(&self) -> Guard<'_, Arc<T>> {
+        loop {
+            if let Some(guard) = self.current.lock() {
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<T> From<Arc<T>> for ArcLock<T> {
+    fn from(data: Arc<T>) -> Self {
+        ArcLock {
+            current: AtomicLock::new(data),
+            writer: AtomicLock::new(()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> From<T> for ArcLock<T> {
+    fn from(data: T) -> Self {
+        ArcLock::new(data)
+    }
+}
+
+impl<T: Default> Default for ArcLock<T> {
+    fn default() -> Self {
+        ArcLock::new(T::default())
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for ArcLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ArcLock")
+            .field("data", &self.load())
+            .finish()
+    }
+}
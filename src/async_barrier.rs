@@ -0,0 +1,95 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! An async barrier. */
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use crate::barrier::{Arrival, TryBarrier};
+use crate::waker_queue::{Registration, WakerQueue};
+
+/**
+A [TryBarrier] whose [AsyncBarrier::wait] resolves once all the participants have arrived.
+
+Like [TryBarrier], it's reusable: as soon as the last participant arrives, the barrier is
+ready for the next phase.
+
+# Cancellation
+Dropping a [BarrierWait] never holds the others up.  Before it's polled, its participant hasn't
+arrived.  Once it has, the arrival counts, so the others are released when everyone else arrives,
+whether or not the dropped future was woken first.
+*/
+#[derive(Debug)]
+pub struct AsyncBarrier {
+    barrier: TryBarrier,
+    waiters: WakerQueue,
+}
+
+impl AsyncBarrier {
+    /**
+    Creates a new barrier for `participants`.
+
+    # Panics
+    If `participants` is 0, or doesn't fit in half a `usize`.
+    */
+    pub const fn new(participants: usize) -> Self {
+        AsyncBarrier { barrier: TryBarrier::new(participants), waiters: WakerQueue::new() }
+    }
+
+    /**
+    Arrives at the barrier and waits for everyone else.
+
+    The participant arrives when the future is first polled.  Dropping the future after that
+    doesn't take the arrival back.
+    */
+    pub fn wait(&self) -> BarrierWait<'_> {
+        BarrierWait { barrier: self, arrival: None, registration: Registration::new(&self.waiters) }
+    }
+
+    /**
+    The number of participants.
+    */
+    pub fn participants(&self) -> usize {
+        self.barrier.participants()
+    }
+}
+
+/**
+Future returned by [AsyncBarrier::wait].
+*/
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct BarrierWait<'a> {
+    barrier: &'a AsyncBarrier,
+    arrival: Option<Arrival>,
+    registration: Registration<'a>,
+}
+
+impl<'a> Future for BarrierWait<'a> {
+    type Output = Arrival;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Arrival> {
+        let barrier = self.barrier;
+        let arrival = match self.arrival {
+            Some(arrival) => arrival,
+            None => {
+                let arrival = barrier.barrier.arrive();
+                if arrival.is_last() {
+                    barrier.waiters.wake_all();
+                    return Poll::Ready(arrival);
+                }
+                self.arrival = Some(arrival);
+                arrival
+            }
+        };
+        if barrier.barrier.is_released(&arrival) {
+            self.registration.complete();
+            return Poll::Ready(arrival);
+        }
+        self.registration.register(cx.waker());
+        if barrier.barrier.is_released(&arrival) {
+            self.registration.complete();
+            Poll::Ready(arrival)
+        } else {
+            Poll::Pending
+        }
+    }
+}
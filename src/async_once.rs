@@ -0,0 +1,183 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! An async once-initialized cell. */
+
+use core::cell::UnsafeCell;
+use core::future::Future;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+use crate::{AtomicLock, Guard};
+use crate::waker_queue::{Registration, WakerQueue};
+
+/**
+A cell that is initialized at most once, by an async initializer, and read lock-free after.
+
+Concurrent callers of [AsyncOnceCell::get_or_init] share one initialization: the first runs
+its initializer while holding a lock, the rest wait for it and get the same value.
+If the initializer panics or its future is dropped, a waiting caller runs its own instead.
+*/
+pub struct AsyncOnceCell<T> {
+    //held while initializing
+    init: AtomicLock<()>,
+    done: AtomicBool,
+    waiters: WakerQueue,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> AsyncOnceCell<T> {
+    /**
+    Creates a new, uninitialized cell.
+    */
+    pub const fn new() -> Self {
+        AsyncOnceCell {
+            init: AtomicLock::new(()),
+            done: AtomicBool::new(false),
+            waiters: WakerQueue::new(),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /**
+    The value, if the cell has been initialized.  A single atomic load.
+    */
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        if self.done.load(Ordering::Acquire) {
+            Some(unsafe { (*self.data.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /**
+    The value, initializing the cell with `f` if nobody has.
+
+    If another caller is initializing it, waits for them instead; `f` is not called.
+    */
+    pub async fn get_or_init<F, Fut>(&self, f: F) -> &T where F: FnOnce() -> Fut, Fut: Future<Output = T> {
+        let claim = match (Claim { cell: self, registration: Registration::new(&self.waiters) }).await {
+            Some(claim) => claim,
+            None => return unsafe { (*self.data.get()).assume_init_ref() },
+        };
+        let data = f().await;
+        let data: &T = unsafe { (*self.data.get()).write(data) };
+        self.done.store(true, Ordering::Release);
+        //wakes everyone waiting for us
+        drop(claim);
+        data
+    }
+
+    /**
+    The value, mutably, if the cell has been initialized.
+    */
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if *self.done.get_mut() {
+            Some(unsafe { self.data.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Consumes the cell, returning the value if it was initialized.
+    */
+    pub fn into_inner(self) -> Option<T> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        if *this.done.get_mut() {
+            Some(unsafe { this.data.get_mut().assume_init_read() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Drop for AsyncOnceCell<T> {
+    fn drop(&mut self) {
+        if *self.done.get_mut() {
+            unsafe { self.data.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+impl<T> Default for AsyncOnceCell<T> {
+    fn default() -> Self {
+        AsyncOnceCell::new()
+    }
+}
+
+impl<T> From<T> for AsyncOnceCell<T> {
+    fn from(data: T) -> Self {
+        AsyncOnceCell {
+            init: AtomicLock::new(()),
+            done: AtomicBool::new(true),
+            waiters: WakerQueue::new(),
+            data: UnsafeCell::new(MaybeUninit::new(data)),
+        }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for AsyncOnceCell<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.get() {
+            Some(data) => f.debug_tuple("AsyncOnceCell").field(data).finish(),
+            None => f.write_str("AsyncOnceCell(<uninit>)"),
+        }
+    }
+}
+
+//as AtomicLazyCell
+unsafe impl<T: Send> Send for AsyncOnceCell<T> {}
+unsafe impl<T: Send + Sync> Sync for AsyncOnceCell<T> {}
+
+//waits until the cell is initialized (None), or we get to initialize it
+struct Claim<'a, T> {
+    cell: &'a AsyncOnceCell<T>,
+    registration: Registration<'a>,
+}
+
+impl<'a, T> Claim<'a, T> {
+    fn attempt(&self) -> Option<Option<InitGuard<'a, T>>> {
+        let cell = self.cell;
+        if cell.done.load(Ordering::Acquire) {
+            return Some(None);
+        }
+        let guard = cell.init.lock()?;
+        //whoever held it before us may have finished
+        if cell.done.load(Ordering::Acquire) {
+            return Some(None);
+        }
+        Some(Some(InitGuard { cell, guard: ManuallyDrop::new(guard) }))
+    }
+}
+
+impl<'a, T> Future for Claim<'a, T> {
+    type Output = Option<InitGuard<'a, T>>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(claim) = self.attempt() {
+            self.registration.complete();
+            return Poll::Ready(claim);
+        }
+        self.registration.register(cx.waker());
+        match self.attempt() {
+            Some(claim) => {
+                self.registration.complete();
+                Poll::Ready(claim)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+//the right to initialize; unlocks, then wakes the waiters to read the value or take over
+struct InitGuard<'a, T> {
+    cell: &'a AsyncOnceCell<T>,
+    guard: ManuallyDrop<Guard<'a, ()>>,
+}
+
+impl<'a, T> Drop for InitGuard<'a, T> {
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        self.cell.waiters.wake_all();
+    }
+}
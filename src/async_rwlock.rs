@@ -0,0 +1,318 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! An async reader-writer lock. */
+
+use core::cell::UnsafeCell;
+use core::fmt::{Debug, Display};
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll};
+use crate::rwlock::{RawAtomicRwLock, WRITER};
+use crate::waker_queue::{Registration, WakerQueue};
+
+/**
+A reader-writer lock whose [AsyncRwLock::read] and [AsyncRwLock::write] wait asynchronously.
+
+Waiting tasks are queued and woken on unlock, so they don't poll in the meantime; this works with
+any executor.  Writers are preferred: once a writer is waiting, new readers wait behind it,
+so a steady stream of readers can't starve writers.
+*/
+pub struct AsyncRwLock<T> {
+    raw: RawAtomicRwLock,
+    //pending write futures; while nonzero, new readers wait
+    writers_waiting: AtomicUsize,
+    readers: WakerQueue,
+    writers: WakerQueue,
+    data: UnsafeCell<T>,
+}
+
+impl<T> AsyncRwLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        AsyncRwLock {
+            raw: RawAtomicRwLock::new(),
+            writers_waiting: AtomicUsize::new(0),
+            readers: WakerQueue::new(),
+            writers: WakerQueue::new(),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /**
+    Locks the lock for reading, if no writer holds it or is waiting for it.
+    */
+    pub fn try_read(&self) -> Option<AsyncReadGuard<'_, T>> {
+        if self.writers_waiting.load(Ordering::SeqCst) == 0 && self.raw.try_read() {
+            Some(AsyncReadGuard { lock: self })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Locks the lock for writing, if nobody else holds it.
+    */
+    pub fn try_write(&self) -> Option<AsyncWriteGuard<'_, T>> {
+        if self.raw.try_write() {
+            Some(AsyncWriteGuard { lock: self })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Locks the lock for reading, waiting for writers to finish.
+
+    # Cancellation
+    Dropping the future gives up its place in the queue.  If an unlock had already woken it,
+    the wakeup passes to the next waiting reader, so cancelling never strands the lock.
+    */
+    pub fn read(&self) -> Read<'_, T> {
+        Read { lock: self, registration: Registration::new(&self.readers) }
+    }
+
+    /**
+    Locks the lock for writing, waiting for everyone else to finish.
+
+    # Cancellation
+    Dropping the future gives up its place in the queue, and stops it keeping new readers out.
+    If an unlock had already woken it, the wakeup passes to the next waiting writer, or to the
+    waiting readers if there is none, so cancelling never strands the lock.
+    */
+    pub fn write(&self) -> Write<'_, T> {
+        Write { lock: self, registration: Registration::new(&self.writers), counted: false }
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: Debug> Debug for AsyncRwLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        //not try_read, which would defer to waiting writers
+        let guard = if self.raw.try_read() { Some(AsyncReadGuard { lock: self }) } else { None };
+        match guard {
+            None => {
+                f.debug_struct("AsyncRwLock")
+                    .field("write_locked", &true)
+                    .field("data", &format_args!("<locked>"))
+                    .finish()
+            }
+            Some(guard) => {
+                f.debug_struct("AsyncRwLock")
+                    .field("write_locked", &false)
+                    .field("data", &*guard)
+                    .finish()
+            }
+        }
+    }
+}
+
+impl<T> Default for AsyncRwLock<T> where T: Default {
+    fn default() -> Self {
+        AsyncRwLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for AsyncRwLock<T> {
+    fn from(data: T) -> Self {
+        AsyncRwLock::new(data)
+    }
+}
+
+//as AtomicRwLock
+unsafe impl<T: Send> Send for AsyncRwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for AsyncRwLock<T> {}
+
+/**
+Future returned by [AsyncRwLock::read].
+*/
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Read<'a, T> {
+    lock: &'a AsyncRwLock<T>,
+    registration: Registration<'a>,
+}
+
+impl<'a, T> Future for Read<'a, T> {
+    type Output = AsyncReadGuard<'a, T>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let lock = self.lock;
+        if let Some(guard) = lock.try_read() {
+            self.registration.complete();
+            return Poll::Ready(guard);
+        }
+        self.registration.register(cx.waker());
+        match lock.try_read() {
+            Some(guard) => {
+                self.registration.complete();
+                Poll::Ready(guard)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+/**
+Future returned by [AsyncRwLock::write].
+*/
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Write<'a, T> {
+    lock: &'a AsyncRwLock<T>,
+    registration: Registration<'a>,
+    //whether we count in writers_waiting
+    counted: bool,
+}
+
+impl<'a, T> Write<'a, T> {
+    fn acquired(&mut self, guard: AsyncWriteGuard<'a, T>) -> Poll<AsyncWriteGuard<'a, T>> {
+        if self.counted {
+            self.counted = false;
+            //readers are still kept out by our write lock, and get woken when we unlock
+            self.lock.writers_waiting.fetch_sub(1, Ordering::SeqCst);
+        }
+        self.registration.complete();
+        Poll::Ready(guard)
+    }
+}
+
+impl<'a, T> Future for Write<'a, T> {
+    type Output = AsyncWriteGuard<'a, T>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let lock = self.lock;
+        if let Some(guard) = lock.try_write() {
+            return self.acquired(guard);
+        }
+        if !self.counted {
+            self.counted = true;
+            lock.writers_waiting.fetch_add(1, Ordering::SeqCst);
+        }
+        self.registration.register(cx.waker());
+        match lock.try_write() {
+            Some(guard) => self.acquired(guard),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<'a, T> Drop for Write<'a, T> {
+    fn drop(&mut self) {
+        //a cancelled writer may have been all that kept readers waiting
+        if self.counted && self.lock.writers_waiting.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.lock.readers.wake_all();
+        }
+    }
+}
+
+/**
+A shared guard for [AsyncRwLock].
+
+Unlocks when dropped, waking a waiting writer if this was the last reader.
+*/
+#[must_use]
+pub struct AsyncReadGuard<'a, T> {
+    lock: &'a AsyncRwLock<T>,
+}
+
+impl<'a, T> Drop for AsyncReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.raw.unlock_read();
+        if self.lock.raw.load() & !WRITER == 0 {
+            self.lock.writers.wake_one();
+        }
+    }
+}
+
+impl<'a, T> core::ops::Deref for AsyncReadGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> AsRef<T> for AsyncReadGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> Debug for AsyncReadGuard<'a, T> where T: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncReadGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> Display for AsyncReadGuard<'a, T> where T: Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/**
+An exclusive guard for [AsyncRwLock].
+
+Unlocks when dropped, waking the next writer, or else the waiting readers.
+*/
+#[must_use]
+pub struct AsyncWriteGuard<'a, T> {
+    lock: &'a AsyncRwLock<T>,
+}
+
+impl<'a, T> Drop for AsyncWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.raw.unlock_write();
+        if self.lock.writers_waiting.load(Ordering::SeqCst) != 0 {
+            self.lock.writers.wake_one();
+        } else {
+            self.lock.readers.wake_all();
+        }
+    }
+}
+
+impl<'a, T> core::ops::Deref for AsyncWriteGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for AsyncWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T> AsRef<T> for AsyncWriteGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> AsMut<T> for AsyncWriteGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T> Debug for AsyncWriteGuard<'a, T> where T: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncWriteGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> Display for AsyncWriteGuard<'a, T> where T: Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
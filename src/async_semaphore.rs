@@ -0,0 +1,132 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! An async counting semaphore. */
+
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll};
+use crate::waker_queue::{Registration, WakerQueue};
+
+/**
+A counting semaphore whose [AsyncSemaphore::acquire] waits asynchronously for a permit.
+
+Works with any executor: waiting tasks are queued and woken as permits are returned.
+*/
+#[derive(Debug)]
+pub struct AsyncSemaphore {
+    permits: AtomicUsize,
+    waiters: WakerQueue,
+}
+
+impl AsyncSemaphore {
+    /**
+    Creates a semaphore with `permits` permits available.
+    */
+    pub const fn new(permits: usize) -> Self {
+        AsyncSemaphore { permits: AtomicUsize::new(permits), waiters: WakerQueue::new() }
+    }
+
+    /**
+    The number of permits available right now.
+    */
+    pub fn available_permits(&self) -> usize {
+        self.permits.load(Ordering::Relaxed)
+    }
+
+    /**
+    Takes a permit if one is available, without waiting.
+    */
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let mut permits = self.permits.load(Ordering::Relaxed);
+        loop {
+            if permits == 0 {
+                return None;
+            }
+            //acquire pairs with the release in add_permits, so we see what the old holder did
+            match self.permits.compare_exchange_weak(permits, permits - 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return Some(SemaphorePermit { semaphore: self }),
+                Err(actual) => permits = actual,
+            }
+        }
+    }
+
+    /**
+    Takes a permit, waiting for one to be returned if none are available.
+
+    # Cancellation
+    Dropping the future gives up its place in the queue.  If a returned permit had already woken
+    it, the wakeup passes to the next waiting task, so cancelling never strands a permit.
+    */
+    pub fn acquire(&self) -> Acquire<'_> {
+        Acquire { semaphore: self, registration: Registration::new(&self.waiters) }
+    }
+
+    /**
+    Adds `n` permits, waking waiting tasks to take them.
+    */
+    pub fn add_permits(&self, n: usize) {
+        self.permits.fetch_add(n, Ordering::Release);
+        if n == 1 {
+            self.waiters.wake_one();
+        } else if n > 1 {
+            self.waiters.wake_all();
+        }
+    }
+}
+
+/**
+Future returned by [AsyncSemaphore::acquire].
+*/
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Acquire<'a> {
+    semaphore: &'a AsyncSemaphore,
+    registration: Registration<'a>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = SemaphorePermit<'a>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore;
+        if let Some(permit) = semaphore.try_acquire() {
+            self.registration.complete();
+            return Poll::Ready(permit);
+        }
+        self.registration.register(cx.waker());
+        match semaphore.try_acquire() {
+            Some(permit) => {
+                self.registration.complete();
+                Poll::Ready(permit)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+/**
+A permit from an [AsyncSemaphore].
+
+Returns the permit when dropped, waking a waiting task if there is one.
+*/
+#[derive(Debug)]
+#[must_use]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a AsyncSemaphore,
+}
+
+impl<'a> SemaphorePermit<'a> {
+    /**
+    Keeps the permit taken for good, so the semaphore has one fewer.
+
+    This is an associated function, for symmetry with the guards.
+    */
+    pub fn forget(permit: Self) {
+        core::mem::forget(permit);
+    }
+}
+
+impl<'a> Drop for SemaphorePermit<'a> {
+    fn drop(&mut self) {
+        self.semaphore.add_permits(1);
+    }
+}
@@ -0,0 +1,110 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A minimal, executor-agnostic async path. */
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use crate::{AtomicLock, Guard};
+
+impl<T> AtomicLock<T> {
+    /**
+    Locks the lock asynchronously, awaiting `yield_fn()` between failed attempts.
+
+    `yield_fn` is your executor's idea of yielding, e.g. `tokio::task::yield_now`.
+    If you don't have one, [yield_now] works on any executor.
+
+    Nothing is registered anywhere, so this needs no allocation, and dropping the future
+    at any point is harmless.  The tradeoff is that the task stays runnable while it waits.
+    */
+    pub async fn lock_async_yield<F, Fut>(&self, mut yield_fn: F) -> Guard<'_, T> where F: FnMut() -> Fut, Fut: Future<Output = ()> {
+        loop {
+            if let Some(guard) = self.lock() {
+                return guard;
+            }
+            yield_fn().await;
+        }
+    }
+}
+
+/**
+Returns a future that is pending exactly once, waking itself immediately.
+
+This gives the executor a chance to run other tasks, without knowing anything about the executor.
+*/
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/**
+Future returned by [yield_now].
+*/
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/**
+With the `async` feature, `lock.await` locks an [AtomicLock], yielding to the executor between
+failed attempts, as [AtomicLock::lock_async_yield] does with [yield_now].
+
+An [AtomicLock] has no queue of waiters, so there is nobody to wake us when it's unlocked; the
+task stays runnable while it waits.  For a lock that queues its tasks, see [crate::AsyncRwLock].
+
+With the `tokio` feature, taking the lock spends a unit of tokio's cooperative budget, as tokio's
+own primitives do.  A task that takes a free lock over and over then yields once its budget runs out,
+rather than starving the other tasks on its worker.  Outside a tokio runtime, nothing changes.
+*/
+#[cfg(feature = "async")]
+impl<'a, T, S: crate::StateWord> core::future::IntoFuture for &'a AtomicLock<T, S> {
+    type Output = Guard<'a, T, S>;
+    type IntoFuture = LockFuture<'a, T, S>;
+    fn into_future(self) -> Self::IntoFuture {
+        LockFuture { lock: self }
+    }
+}
+
+/**
+Future returned by awaiting an [AtomicLock].
+*/
+#[cfg(feature = "async")]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LockFuture<'a, T, S: crate::StateWord = crate::State> {
+    lock: &'a AtomicLock<T, S>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T, S: crate::StateWord> Future for LockFuture<'a, T, S> {
+    type Output = Guard<'a, T, S>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        //out of budget; tokio wakes us when it's our turn again
+        #[cfg(feature = "tokio")]
+        let coop = core::task::ready!(tokio::task::coop::poll_proceed(cx));
+        match self.lock.lock() {
+            Some(guard) => {
+                //a failed attempt gives the budget back when coop drops
+                #[cfg(feature = "tokio")]
+                coop.made_progress();
+                Poll::Ready(guard)
+            }
+            None => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Lock attempts as an iterator. */
+
+use crate::{AtomicLock, Guard, StateWord};
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    An endless iterator of attempts to lock the lock, each the result of [AtomicLock::lock].
+
+    Drive it with your own combinators instead of a hand-written loop, e.g.
+    `lock.lock_attempts().take(100).flatten().next()` to try a hundred times.
+    */
+    pub fn lock_attempts(&self) -> LockAttempts<'_, T, S> {
+        LockAttempts { lock: self }
+    }
+}
+
+/**
+The iterator returned by [AtomicLock::lock_attempts].
+*/
+#[derive(Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct LockAttempts<'a, T, S: StateWord = crate::state::State> {
+    lock: &'a AtomicLock<T, S>,
+}
+
+impl<'a, T, S: StateWord> Iterator for LockAttempts<'a, T, S> {
+    type Item = Option<Guard<'a, T, S>>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.lock.lock())
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl<'a, T, S: StateWord> core::iter::FusedIterator for LockAttempts<'a, T, S> {}
+
+impl<'a, T, S: StateWord> Clone for LockAttempts<'a, T, S> {
+    fn clone(&self) -> Self {
+        LockAttempts { lock: self.lock }
+    }
+}
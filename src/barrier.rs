@@ -0,0 +1,92 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A reusable barrier, without blocking. */
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+//the state is a generation in the high half, and the arrivals so far in the low half
+const COUNT_BITS: u32 = usize::BITS / 2;
+const COUNT_MASK: usize = (1 << COUNT_BITS) - 1;
+
+/**
+A barrier for a fixed number of participants, reusable for each phase of a phased algorithm.
+
+Each participant calls [TryBarrier::arrive], then polls [TryBarrier::is_released] with its
+[Arrival] until everyone has arrived.  Nothing blocks; what you do while polling is up to you.
+As soon as the last participant arrives, the barrier is ready for the next phase.
+*/
+#[derive(Debug)]
+pub struct TryBarrier {
+    state: AtomicUsize,
+    participants: usize,
+}
+
+impl TryBarrier {
+    /**
+    Creates a new barrier for `participants`.
+
+    # Panics
+    If `participants` is 0, or doesn't fit in half a `usize`.
+    */
+    pub const fn new(participants: usize) -> Self {
+        assert!(participants != 0 && participants <= COUNT_MASK, "unsupported number of participants");
+        TryBarrier { state: AtomicUsize::new(0), participants }
+    }
+
+    /**
+    Arrives at the barrier for the current phase.
+
+    The result says whether we were the last to arrive, which releases everyone.
+    */
+    pub fn arrive(&self) -> Arrival {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            let generation = state >> COUNT_BITS;
+            let count = (state & COUNT_MASK) + 1;
+            let last = count == self.participants;
+            let new = if last {
+                generation.wrapping_add(1) << COUNT_BITS
+            } else {
+                generation << COUNT_BITS | count
+            };
+            //acquire what the earlier arrivals did, and release it, with ours, to the pollers
+            match self.state.compare_exchange_weak(state, new, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => return Arrival { generation, last },
+                Err(actual) => state = actual,
+            }
+        }
+    }
+
+    /**
+    Whether everyone has arrived for the phase of `arrival`.
+
+    Once this returns true, everything the participants did before arriving is visible.
+    */
+    pub fn is_released(&self, arrival: &Arrival) -> bool {
+        self.state.load(Ordering::Acquire) >> COUNT_BITS != arrival.generation
+    }
+
+    /**
+    The number of participants.
+    */
+    pub fn participants(&self) -> usize {
+        self.participants
+    }
+}
+
+/**
+A participant's arrival at a [TryBarrier], from [TryBarrier::arrive].
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arrival {
+    generation: usize,
+    last: bool,
+}
+
+impl Arrival {
+    /**
+    Whether this participant was the last to arrive, releasing the others.
+    */
+    pub fn is_last(&self) -> bool {
+        self.last
+    }
+}
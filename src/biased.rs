@@ -0,0 +1,221 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locks biased toward one dominant thread. */
+
+use core::cell::Cell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+An [AtomicLock] that can be biased toward one thread, which then locks it with plain loads.
+
+[BiasedLock::bias] hands out a [BiasHandle], which keeps the lock held on its behalf.  Locking
+through the handle is then a check of a flag, with no read-modify-write, for as long as nobody
+else wants the lock.  When another thread's [BiasedLock::lock] finds it biased, it asks for the
+bias to be revoked, and returns None.  The handle gives the bias up the next time it locks or
+unlocks, after which the lock works like a plain [AtomicLock], for the handle too.
+
+Since revoking waits on the handle, a handle that stops locking keeps the lock forever; drop it
+when the dominant thread is done.
+*/
+#[derive(Debug, Default)]
+pub struct BiasedLock<T> {
+    lock: AtomicLock<T>,
+    //whether a handle holds the lock.  Only written by whoever holds the lock.
+    biased: AtomicBool,
+    //set by others when they want the handle to give up the lock
+    revoke: AtomicBool,
+}
+
+impl<T> BiasedLock<T> {
+    /**
+    Creates a new, unbiased lock.
+    */
+    pub const fn new(data: T) -> Self {
+        BiasedLock { lock: AtomicLock::new(data), biased: AtomicBool::new(false), revoke: AtomicBool::new(false) }
+    }
+
+    /**
+    Biases the lock toward the caller, if it's available.
+
+    Lock through the returned handle from the dominant thread.  If the lock is unavailable,
+    will return None.
+    */
+    pub fn bias(&self) -> Option<BiasHandle<'_, T>> {
+        //the handle holds the lock from now on, and unlocks it in unbias
+        core::mem::forget(self.lock.lock()?);
+        self.revoke.store(false, Ordering::Relaxed);
+        //pairs with the Acquire in lock, so a revoke request for this bias isn't overwritten by the store above
+        self.biased.store(true, Ordering::Release);
+        Some(BiasHandle { lock: self, biased: Cell::new(true), held: Cell::new(false) })
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+
+    If a [BiasHandle] holds the lock, asks it to give it up; try again after it next locks or unlocks.
+    */
+    pub fn lock(&self) -> Option<Guard<'_, T>> {
+        let guard = self.lock.lock();
+        if guard.is_none() && self.biased.load(Ordering::Acquire) {
+            self.revoke.store(true, Ordering::Relaxed);
+        }
+        guard
+    }
+
+    /**
+    Whether a [BiasHandle] holds the lock right now.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_biased(&self) -> bool {
+        self.biased.load(Ordering::Relaxed)
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+/**
+The dominant thread's handle to a [BiasedLock], from [BiasedLock::bias].
+
+While the lock is biased, the handle holds it, and [BiasHandle::lock] is a plain check.
+Dropping the handle gives up the bias.
+*/
+pub struct BiasHandle<'a, T> {
+    lock: &'a BiasedLock<T>,
+    //whether we hold the lock
+    biased: Cell<bool>,
+    //whether a BiasedGuard borrows the data while biased
+    held: Cell<bool>,
+}
+
+impl<'a, T> BiasHandle<'a, T> {
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+
+    While the lock is biased, this doesn't touch any atomics, other than to check whether
+    someone asked for the bias to be revoked.  If they did, gives the bias up, and locks the
+    lock the ordinary way.
+    */
+    pub fn lock(&self) -> Option<BiasedGuard<'_, T>> {
+        if self.held.get() {
+            return None;
+        }
+        if self.biased.get() {
+            if !self.lock.revoke.load(Ordering::Relaxed) {
+                self.held.set(true);
+                //we hold the lock, and held keeps anyone else here from borrowing the data too
+                let data = unsafe { &mut *self.lock.lock.data.get() };
+                return Some(BiasedGuard { inner: Inner::Biased(self, data) });
+            }
+            self.unbias();
+        }
+        self.lock.lock.lock().map(|guard| BiasedGuard { inner: Inner::Locked(guard) })
+    }
+
+    /**
+    Whether this handle still holds the bias.
+    */
+    pub fn is_biased(&self) -> bool {
+        self.biased.get()
+    }
+
+    //gives up the bias; there must be no BiasedGuard borrowing the data
+    fn unbias(&self) {
+        self.biased.set(false);
+        self.lock.biased.store(false, Ordering::Relaxed);
+        //releases, so whoever locks next sees what we wrote while biased
+        crate::unlock(&self.lock.lock.lock);
+    }
+}
+
+impl<'a, T> Drop for BiasHandle<'a, T> {
+    fn drop(&mut self) {
+        if self.biased.get() {
+            self.unbias();
+        }
+    }
+}
+
+impl<'a, T> core::fmt::Debug for BiasHandle<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BiasHandle").field("biased", &self.is_biased()).finish_non_exhaustive()
+    }
+}
+
+/**
+A guard for a [BiasHandle].
+
+Unlocks when dropped, and gives up the bias if someone asked for it.
+*/
+#[must_use]
+pub struct BiasedGuard<'h, T> {
+    inner: Inner<'h, T>,
+}
+
+enum Inner<'h, T> {
+    Biased(&'h BiasHandle<'h, T>, &'h mut T),
+    Locked(Guard<'h, T>),
+}
+
+impl<'h, T> Drop for BiasedGuard<'h, T> {
+    fn drop(&mut self) {
+        if let Inner::Biased(handle, _) = self.inner {
+            handle.held.set(false);
+            if handle.lock.revoke.load(Ordering::Relaxed) {
+                //our &mut T is dead after this drop, so nothing borrows the data anymore
+                handle.unbias();
+            }
+        }
+    }
+}
+
+impl<'h, T> Deref for BiasedGuard<'h, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        match &self.inner {
+            Inner::Biased(_, data) => data,
+            Inner::Locked(guard) => guard,
+        }
+    }
+}
+
+impl<'h, T> DerefMut for BiasedGuard<'h, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match &mut self.inner {
+            Inner::Biased(_, data) => data,
+            Inner::Locked(guard) => guard,
+        }
+    }
+}
+
+impl<'h, T> AsRef<T> for BiasedGuard<'h, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'h, T> AsMut<T> for BiasedGuard<'h, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'h, T: core::fmt::Debug> core::fmt::Debug for BiasedGuard<'h, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BiasedGuard").field("data", &**self).finish()
+    }
+}
+
+impl<'h, T: core::fmt::Display> core::fmt::Display for BiasedGuard<'h, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,116 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A pool of tiny locks, packed 64 to an atomic word. */
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/**
+A pool of `64 * WORDS` locks, each one a single bit.
+
+These locks don't protect any data themselves; use them when you have many items (rows, entities)
+and one `AtomicBool` per item is too much memory.
+
+Locks that share a word share a cache line, so heavy contention on one bit slows down its neighbours.
+*/
+#[derive(Debug)]
+pub struct BitLockPool<const WORDS: usize> {
+    words: [AtomicU64; WORDS],
+}
+
+impl<const WORDS: usize> BitLockPool<WORDS> {
+    /**
+    Creates a new pool with every lock unlocked.
+    */
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const UNLOCKED: AtomicU64 = AtomicU64::new(0);
+        BitLockPool {
+            words: [UNLOCKED; WORDS],
+        }
+    }
+
+    /**
+    The number of locks in the pool.
+    */
+    pub const fn capacity(&self) -> usize {
+        WORDS * 64
+    }
+
+    /**
+    Locks the lock at `index` if available.
+    If the lock is unavailable, will return None.
+
+    # Panics
+    Panics if `index` is out of bounds.
+    */
+    pub fn try_lock(&self, index: usize) -> Option<BitLockGuard<'_>> {
+        let (word, bit) = self.position(index);
+        //test before test-and-set, as in AtomicLock; a held bit costs a shared read, not a write
+        if word.load(Ordering::Relaxed) & bit != 0 {
+            return None;
+        }
+        let old = word.fetch_or(bit, Ordering::Acquire);
+        if old & bit == 0 {
+            Some(BitLockGuard { word, bit, index })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Whether the lock at `index` is currently held.
+
+    The answer may be stale by the time you read it.
+
+    # Panics
+    Panics if `index` is out of bounds.
+    */
+    pub fn is_locked(&self, index: usize) -> bool {
+        let (word, bit) = self.position(index);
+        word.load(Ordering::Relaxed) & bit != 0
+    }
+
+    fn position(&self, index: usize) -> (&AtomicU64, u64) {
+        assert!(index < self.capacity(), "index {} out of bounds for pool of {}", index, self.capacity());
+        (&self.words[index / 64], 1 << (index % 64))
+    }
+}
+
+impl<const WORDS: usize> Default for BitLockPool<WORDS> {
+    fn default() -> Self {
+        BitLockPool::new()
+    }
+}
+
+/**
+A guard for one lock in a [BitLockPool].
+
+Unlocks when dropped.
+*/
+#[derive(Debug)]
+#[must_use]
+pub struct BitLockGuard<'a> {
+    word: &'a AtomicU64,
+    bit: u64,
+    index: usize,
+}
+
+impl<'a> BitLockGuard<'a> {
+    /**
+    The index of the lock this guard holds.
+    */
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<'a> Drop for BitLockGuard<'a> {
+    fn drop(&mut self) {
+        #[cfg(any(debug_assertions, feature = "strict"))]
+        {
+            let old = self.word.fetch_and(!self.bit, Ordering::Release);
+            assert!(old & self.bit != 0, "unlocked a bit lock that was not locked");
+        }
+        #[cfg(not(any(debug_assertions, feature = "strict")))]
+        self.word.fetch_and(!self.bit, Ordering::Release);
+    }
+}
@@ -0,0 +1,83 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Constructing locks on the heap, and taking shared ones apart again. */
+
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use alloc::boxed::Box;
+use core::mem::MaybeUninit;
+use crate::AtomicLock;
+#[cfg(target_has_atomic = "ptr")]
+use crate::StateWord;
+
+impl<T> AtomicLock<T> {
+    /**
+    Creates a new lock on the heap.
+
+    `Box::new(AtomicLock::new(data))` builds the lock on the stack and then copies it to the heap,
+    which, for a multi-kilobyte `T`, can blow a small embedded stack.  This allocates first and
+    moves `data` straight into place.
+    */
+    pub fn new_boxed(data: T) -> Box<Self> {
+        //Safety: we initialize the data
+        unsafe {
+            AtomicLock::new_in_place(|slot| {
+                slot.write(data);
+            })
+        }
+    }
+
+    /**
+    Creates a new lock on the heap, with `f` initializing the data where it will live.
+
+    The data never exists by value, so even a payload too big for the stack works,
+    as long as `f` initializes it piece by piece.
+
+    If `f` panics, the allocation is freed, and whatever `f` initialized is leaked.
+
+    # Safety
+    `f` must fully initialize the data.
+    */
+    pub unsafe fn new_in_place<F>(f: F) -> Box<Self> where F: FnOnce(&mut MaybeUninit<T>) {
+        //frees the allocation if f panics
+        struct Allocation(*mut u8, Layout);
+        impl Drop for Allocation {
+            fn drop(&mut self) {
+                unsafe { dealloc(self.0, self.1) }
+            }
+        }
+
+        let layout = Layout::new::<Self>();
+        //Safety: the layout is never zero-sized, since it includes the state
+        let ptr = alloc(layout);
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+        let allocation = Allocation(ptr, layout);
+        let lock = AtomicLock::init_in_place(&mut *(ptr as *mut MaybeUninit<Self>), f);
+        core::mem::forget(allocation);
+        //Safety: allocated with the global allocator and Self's layout, and now initialized
+        Box::from_raw(lock)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    Returns the data, if this is the last reference to the lock and the lock is free.
+    Otherwise, gives the `Arc` back.
+
+    Checking both at once avoids unwrapping an `Arc` whose lock was left held by a leaked guard,
+    or racing another thread that still has a clone.
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    */
+    pub fn try_unwrap_arc(mut this: alloc::sync::Arc<Self>) -> Result<T, alloc::sync::Arc<Self>> {
+        //get_mut checks for weak references too, so nobody can get another reference after this
+        let free = match alloc::sync::Arc::get_mut(&mut this) {
+            Some(lock) => !lock.is_locked(),
+            None => false,
+        };
+        if !free {
+            return Err(this);
+        }
+        alloc::sync::Arc::try_unwrap(this).map(AtomicLock::into_inner)
+    }
+}
@@ -0,0 +1,142 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Branded-token access, where one lock guards many cells.
+
+Each [BrandLock] has a unique lifetime, its brand.  Locking it returns a [BrandToken],
+and a [BrandCell] with the same brand can only be accessed by showing that token.
+The compiler checks the brands match, so once you hold the token, each access is just a
+pointer dereference, with no atomics.  This is the same idea as GhostCell.
+*/
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use crate::{AtomicLock, Guard};
+
+//invariant, so one brand can't be coerced into another
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+/**
+A lock whose token unlocks every [BrandCell] of the same brand.
+
+Create one with [BrandLock::scope].
+*/
+pub struct BrandLock<'brand> {
+    lock: AtomicLock<()>,
+    _brand: Brand<'brand>,
+}
+
+impl BrandLock<'_> {
+    /**
+    Calls `f` with a new lock, with a brand no other lock has.
+
+    The brand only lives as long as `f`, so the lock and its cells have to be used inside it.
+    Share the lock with other threads by reference, e.g. with `std::thread::scope`.
+    */
+    pub fn scope<R, F>(f: F) -> R where F: for<'brand> FnOnce(BrandLock<'brand>) -> R {
+        f(BrandLock { lock: AtomicLock::new(()), _brand: PhantomData })
+    }
+}
+
+impl<'brand> BrandLock<'brand> {
+    /**
+    Locks the lock and returns the token, if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<BrandToken<'_, 'brand>> {
+        self.lock.lock().map(|guard| BrandToken { _guard: guard, _brand: PhantomData })
+    }
+
+    /**
+    Whether the lock is currently held.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+}
+
+impl core::fmt::Debug for BrandLock<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BrandLock")
+            .field("locked", &self.is_locked())
+            .finish()
+    }
+}
+
+/**
+Proof that a [BrandLock] is held.  Unlocks when dropped.
+
+Show it to a [BrandCell] of the same brand to access the cell's data.
+*/
+#[must_use]
+#[derive(Debug)]
+pub struct BrandToken<'a, 'brand> {
+    _guard: Guard<'a, ()>,
+    _brand: Brand<'brand>,
+}
+
+/**
+Data guarded by the [BrandLock] of the same brand.
+*/
+pub struct BrandCell<'brand, T: ?Sized> {
+    _brand: Brand<'brand>,
+    data: UnsafeCell<T>,
+}
+
+impl<'brand, T> BrandCell<'brand, T> {
+    /**
+    Creates a new cell.  Its brand is inferred from how it's used.
+    */
+    pub const fn new(data: T) -> Self {
+        BrandCell { _brand: PhantomData, data: UnsafeCell::new(data) }
+    }
+
+    /**
+    Consumes the cell, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<'brand, T: ?Sized> BrandCell<'brand, T> {
+    /**
+    Accesses the data, given the token.
+    */
+    pub fn get<'t>(&'t self, _token: &'t BrandToken<'_, 'brand>) -> &'t T {
+        //the token is only handed out while the lock is held, and mutable access needs it uniquely
+        unsafe { &*self.data.get() }
+    }
+
+    /**
+    Accesses the data mutably, given the token.
+
+    This borrows the token uniquely, so only one cell can be borrowed mutably at a time.
+    */
+    #[allow(clippy::mut_from_ref)]
+    pub fn get_mut<'t>(&'t self, _token: &'t mut BrandToken<'_, 'brand>) -> &'t mut T {
+        unsafe { &mut *self.data.get() }
+    }
+}
+
+impl<T: Default> Default for BrandCell<'_, T> {
+    fn default() -> Self {
+        BrandCell::new(T::default())
+    }
+}
+
+impl<T> From<T> for BrandCell<'_, T> {
+    fn from(data: T) -> Self {
+        BrandCell::new(data)
+    }
+}
+
+//the data can't be read without the token, so debug shows nothing
+impl<T: ?Sized> core::fmt::Debug for BrandCell<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BrandCell").finish_non_exhaustive()
+    }
+}
+
+//like a RwLock: the token can be shared for readers, or moved for a writer
+unsafe impl<T: ?Sized + Send + Sync> Sync for BrandCell<'_, T> {}
@@ -0,0 +1,47 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Waiting for a lock, unless cancelled. */
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+The error from [AtomicLock::lock_interruptible] when the wait was cancelled.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cancelled;
+
+impl core::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("waiting for the lock was cancelled")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Cancelled {}
+
+impl<T> AtomicLock<T> {
+    /**
+    Spins until the lock is available, or `cancel` is set.
+
+    Checks the lock first, so if it's available, this succeeds even when cancelled.
+    Set `cancel` from a shutdown path to abort threads stuck waiting.
+    */
+    pub fn lock_interruptible(&self, cancel: &AtomicBool) -> Result<Guard<'_, T>, Cancelled> {
+        if let Some(guard) = self.lock() {
+            return Ok(guard);
+        }
+        #[cfg(feature = "profiling")]
+        let zone = crate::profiling::WaitZone::new(self);
+        loop {
+            if let Some(guard) = self.acquire() {
+                #[cfg(feature = "profiling")]
+                zone.acquired();
+                return Ok(guard);
+            }
+            if cancel.load(Ordering::Relaxed) {
+                return Err(Cancelled);
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
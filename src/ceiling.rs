@@ -0,0 +1,178 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! The priority ceiling protocol, for real-time systems. */
+
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::panic::Location;
+use crate::{AtomicLock, Guard};
+
+/**
+Reads and sets the calling thread's, or task's, priority, for [CeilingLock].
+
+Implement this for your RTOS or scheduler.  `Priority` orders from least to most urgent; if your
+system counts the other way, wrap it in [core::cmp::Reverse].
+*/
+pub trait PriorityHooks {
+    /**
+    A priority.  Greater is more urgent.
+    */
+    type Priority: Copy + Ord;
+    /**
+    The calling thread's current priority.
+    */
+    fn current(&self) -> Self::Priority;
+    /**
+    Sets the calling thread's priority.
+    */
+    fn set(&self, priority: Self::Priority);
+}
+
+/**
+An [AtomicLock] with a priority ceiling: whoever holds it runs at least at the ceiling priority.
+
+Set the ceiling to the priority of the most urgent thread that takes the lock.  Then no thread
+that takes it can preempt the holder, so priority inversion is bounded by one critical section,
+even though nobody ever waits on the lock itself.
+
+[CeilingLock::lock] raises the caller to the ceiling before it tries the lock, so the holder is
+never preempted between acquiring and raising, and puts the priority back if the lock is busy.
+A caller already at or above the ceiling is left alone.  The guard restores the priority it replaced.
+
+Nested locks work as long as guards are dropped in the reverse order they were taken, as scopes do.
+Out of order, the priority would be restored too early; debug builds, or the `strict` feature,
+panic when they notice.
+*/
+#[derive(Debug, Default)]
+pub struct CeilingLock<T, H: PriorityHooks> {
+    lock: AtomicLock<T>,
+    hooks: H,
+    ceiling: H::Priority,
+}
+
+impl<T, H: PriorityHooks> CeilingLock<T, H> {
+    /**
+    Creates a new lock, whose holders run at `ceiling` or above.
+    */
+    pub const fn new(data: T, hooks: H, ceiling: H::Priority) -> Self {
+        CeilingLock { lock: AtomicLock::new(data), hooks, ceiling }
+    }
+
+    /**
+    Locks the lock and accesses the data if available, raising the caller to the ceiling.
+    If the lock is unavailable, will return None, at the caller's old priority.
+    */
+    #[track_caller]
+    pub fn lock(&self) -> Option<CeilingGuard<'_, T, H>> {
+        let current = self.hooks.current();
+        let previous = if current < self.ceiling {
+            self.hooks.set(self.ceiling);
+            Some(current)
+        } else {
+            None
+        };
+        match self.lock.lock() {
+            Some(guard) => Some(CeilingGuard { guard: ManuallyDrop::new(guard), lock: self, previous, site: Location::caller(), _not_send: PhantomData }),
+            None => {
+                if let Some(previous) = previous {
+                    self.hooks.set(previous);
+                }
+                None
+            }
+        }
+    }
+
+    /**
+    The lock's priority ceiling.
+    */
+    pub fn ceiling(&self) -> H::Priority {
+        self.ceiling
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+const CHECKING: bool = cfg!(any(debug_assertions, feature = "strict"));
+
+/**
+A guard for [CeilingLock].
+
+Unlocks when dropped, then restores the priority the lock raised the holder from.
+It's not `Send`, since that priority belongs to the thread that took the lock.
+*/
+#[must_use]
+pub struct CeilingGuard<'a, T, H: PriorityHooks> {
+    guard: ManuallyDrop<Guard<'a, T>>,
+    lock: &'a CeilingLock<T, H>,
+    //the priority we raised from, if we raised
+    previous: Option<H::Priority>,
+    site: &'static Location<'static>,
+    //the priority to restore is this thread's, so the guard can't move to another one
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a, T, H: PriorityHooks> Drop for CeilingGuard<'a, T, H> {
+    fn drop(&mut self) {
+        //unlock before lowering our priority, so we can't be preempted while holding the lock
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        if let Some(previous) = self.previous {
+            //anything else means a guard taken after us is still raising the priority
+            let in_order = !CHECKING || self.lock.hooks.current() == self.lock.ceiling;
+            self.lock.hooks.set(previous);
+            if !in_order {
+                out_of_order(self.site);
+            }
+        }
+    }
+}
+
+#[cold]
+fn out_of_order(site: &'static Location<'static>) {
+    //panicking while already unwinding would abort, which loses the original panic
+    #[cfg(feature = "std")]
+    if std::thread::panicking() {
+        return;
+    }
+    panic!("the guard for the CeilingLock taken at {} was dropped while a later guard still holds its ceiling", site);
+}
+
+impl<'a, T, H: PriorityHooks> core::ops::Deref for CeilingGuard<'a, T, H> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T, H: PriorityHooks> core::ops::DerefMut for CeilingGuard<'a, T, H> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T, H: PriorityHooks> AsRef<T> for CeilingGuard<'a, T, H> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T, H: PriorityHooks> AsMut<T> for CeilingGuard<'a, T, H> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T, H: PriorityHooks> core::fmt::Debug for CeilingGuard<'a, T, H> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CeilingGuard").field("data", &**self).finish()
+    }
+}
+
+impl<'a, T, H: PriorityHooks> core::fmt::Display for CeilingGuard<'a, T, H> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
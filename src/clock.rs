@@ -0,0 +1,92 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Clocks for timeouts, without requiring std. */
+
+use crate::{AtomicLock, Guard};
+
+/**
+A monotonic source of time, in ticks of whatever length is convenient.
+
+Embedded users can implement this for their cycle counter or RTC; any `Fn() -> u64` is a clock already.
+With the `std` feature, `StdClock` counts nanoseconds.
+
+Ticks may wrap around; we only ever look at differences.
+*/
+pub trait Clock {
+    /**
+    The current time, in ticks.
+    */
+    fn now(&self) -> u64;
+}
+
+impl<F> Clock for F where F: Fn() -> u64 {
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+/**
+A [Clock] based on [std::time::Instant], ticking in nanoseconds.
+*/
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock {
+    origin: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    /**
+    Creates a new clock, whose ticks count from now.
+    */
+    pub fn new() -> Self {
+        StdClock { origin: std::time::Instant::now() }
+    }
+
+    /**
+    Converts a duration to ticks of this clock.
+    */
+    pub fn ticks(duration: std::time::Duration) -> u64 {
+        duration.as_nanos() as u64
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        StdClock::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now(&self) -> u64 {
+        self.origin.elapsed().as_nanos() as u64
+    }
+}
+
+impl<T> AtomicLock<T> {
+    /**
+    Spins until the lock is available, or `timeout` ticks of `clock` have passed.
+
+    Tries at least once, so a timeout of 0 behaves like [AtomicLock::lock].
+    */
+    pub fn lock_timeout<C: Clock + ?Sized>(&self, clock: &C, timeout: u64) -> Option<Guard<'_, T>> {
+        if let Some(guard) = self.lock() {
+            return Some(guard);
+        }
+        #[cfg(feature = "profiling")]
+        let zone = crate::profiling::WaitZone::new(self);
+        let start = clock.now();
+        loop {
+            if let Some(guard) = self.acquire() {
+                #[cfg(feature = "profiling")]
+                zone.acquired();
+                return Some(guard);
+            }
+            if clock.now().wrapping_sub(start) >= timeout {
+                return None;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A cohort lock, which keeps a hot lock on one NUMA node while it has takers there. */
+
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use crate::{AtomicLock, Guard};
+use crate::padded::CachePadded;
+
+//how many times in a row the lock can be passed within a node, before it's released to everyone
+const MAX_PASSES: usize = 64;
+
+//nobody holds the global lock
+const FREE: usize = 0;
+
+/**
+A lock for machines with several NUMA nodes, which prefers to pass itself to the next taker
+on the same node.
+
+Each node has a local [AtomicLock], and the nodes share a global one.  To take the lock, you
+take your node's local lock, then the global lock, unless someone on your node passed it to you.
+When you unlock while someone on your node has failed to take the local lock, we keep the global
+lock for your node, and only release the local one, so their next try never leaves the node.
+After 64 passes in a row, we release the global lock anyway, so other nodes get their turn.
+
+If nobody on the node comes back for a passed lock, a try from another node reclaims it, so it
+can't be stranded there.
+
+You say which node you're on; this crate doesn't know about your topology.  Ask your OS, e.g.
+with libnuma or `getcpu`, and cache the answer per thread.
+*/
+pub struct CohortLock<T, const NODES: usize> {
+    //FREE, or 1 + the node that holds it
+    global: CachePadded<AtomicUsize>,
+    nodes: [CachePadded<Node>; NODES],
+    data: UnsafeCell<T>,
+}
+
+struct Node {
+    //the data is how many times in a row the global lock was passed within the node
+    lock: AtomicLock<usize>,
+    //someone on the node failed to take the local lock since it was last passed
+    waiting: AtomicBool,
+}
+
+impl<T, const NODES: usize> CohortLock<T, NODES> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const NODE: CachePadded<Node> = CachePadded(Node { lock: AtomicLock::new(0), waiting: AtomicBool::new(false) });
+        CohortLock {
+            global: CachePadded(AtomicUsize::new(FREE)),
+            nodes: [NODE; NODES],
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available, from NUMA node `node`.
+    If the lock is unavailable, will return None.
+
+    # Panics
+    If `node` is `NODES` or more.
+    */
+    pub fn lock(&self, node: usize) -> Option<CohortGuard<'_, T, NODES>> {
+        let local = &self.nodes[node];
+        let Some(passes) = local.lock.lock() else {
+            local.waiting.store(true, Ordering::Relaxed);
+            return None;
+        };
+        let ours = node + 1;
+        //if it was passed to us, the local lock we just took brings the data with it
+        let global = self.global.load(Ordering::Relaxed);
+        if global != ours {
+            let taken = if global == FREE {
+                //pairs with the Release in CohortGuard::drop
+                self.global.compare_exchange(FREE, ours, Ordering::Acquire, Ordering::Relaxed).is_ok()
+            } else {
+                self.reclaim(global, ours)
+            };
+            if !taken {
+                return None;
+            }
+        }
+        Some(CohortGuard { lock: self, node, local: passes, data: unsafe { &mut *self.data.get() } })
+    }
+
+    //takes the global lock from `holder`, if it was passed within its node and nobody there took it
+    #[cold]
+    fn reclaim(&self, holder: usize, ours: usize) -> bool {
+        //whoever is in the critical section holds their node's local lock, so if we get it, nobody is
+        let Some(mut passes) = self.nodes[holder - 1].lock.lock() else { return false };
+        if self.global.compare_exchange(holder, ours, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return false;
+        }
+        *passes = 0;
+        true
+    }
+
+    /**
+    Whether the lock is held, or kept for a node.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.global.load(Ordering::Relaxed) != FREE
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+unsafe impl<T: Send, const NODES: usize> Send for CohortLock<T, NODES> {}
+unsafe impl<T: Send, const NODES: usize> Sync for CohortLock<T, NODES> {}
+
+impl<T: Default, const NODES: usize> Default for CohortLock<T, NODES> {
+    fn default() -> Self {
+        CohortLock::new(T::default())
+    }
+}
+
+impl<T, const NODES: usize> From<T> for CohortLock<T, NODES> {
+    fn from(data: T) -> Self {
+        CohortLock::new(data)
+    }
+}
+
+impl<T, const NODES: usize> core::fmt::Debug for CohortLock<T, NODES> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CohortLock").field("locked", &self.is_locked()).finish_non_exhaustive()
+    }
+}
+
+/**
+A guard for [CohortLock].
+
+Unlocks when dropped, passing the lock within the node if someone there is waiting for it.
+*/
+#[must_use]
+pub struct CohortGuard<'a, T, const NODES: usize> {
+    lock: &'a CohortLock<T, NODES>,
+    node: usize,
+    local: Guard<'a, usize>,
+    data: &'a mut T,
+}
+
+impl<'a, T, const NODES: usize> Drop for CohortGuard<'a, T, NODES> {
+    fn drop(&mut self) {
+        let node = &self.lock.nodes[self.node];
+        if node.waiting.load(Ordering::Relaxed) && *self.local < MAX_PASSES {
+            node.waiting.store(false, Ordering::Relaxed);
+            *self.local += 1;
+            //unlocking the local lock after this publishes the data to whoever on the node takes it next
+        } else {
+            *self.local = 0;
+            self.lock.global.store(FREE, Ordering::Release);
+        }
+        //the local guard is dropped after us, unlocking the local lock
+    }
+}
+
+impl<'a, T, const NODES: usize> Deref for CohortGuard<'a, T, NODES> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.data
+    }
+}
+
+impl<'a, T, const NODES: usize> DerefMut for CohortGuard<'a, T, NODES> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.data
+    }
+}
+
+impl<'a, T, const NODES: usize> AsRef<T> for CohortGuard<'a, T, NODES> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T, const NODES: usize> AsMut<T> for CohortGuard<'a, T, NODES> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T: core::fmt::Debug, const NODES: usize> core::fmt::Debug for CohortGuard<'a, T, NODES> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CohortGuard").field("data", &**self).finish()
+    }
+}
+
+impl<'a, T: core::fmt::Display, const NODES: usize> core::fmt::Display for CohortGuard<'a, T, NODES> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,214 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Flat combining: queueing operations for the lock holder to run. */
+
+use alloc::boxed::Box;
+use core::mem::ManuallyDrop;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use crate::{AtomicLock, Guard};
+
+type Operation<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+struct Node<T> {
+    operation: Operation<T>,
+    next: *mut Node<T>,
+}
+
+/**
+An [AtomicLock] where a caller who finds the lock busy can leave an operation for the holder to run.
+
+[CombiningLock::apply_or_enqueue] runs the operation right away if the lock is free.  Otherwise it
+pushes it on a queue, without waiting, and whoever holds the lock runs it before unlocking.
+Under heavy contention this beats everyone fighting over the flag, since one thread does
+all the work while the data is hot in its cache.
+
+Queued operations run in the order they were queued.
+
+If an operation panics, the panic unwinds out of whoever ran it, usually the guard's drop, and
+the lock is unlocked.  The operations that were queued behind it in the same batch are dropped
+without running; anything queued later runs with the next holder.
+*/
+pub struct CombiningLock<T> {
+    lock: AtomicLock<T>,
+    //a stack of queued operations, newest first
+    queue: AtomicPtr<Node<T>>,
+}
+
+impl<T> CombiningLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        CombiningLock {
+            lock: AtomicLock::new(data),
+            queue: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+
+    The guard runs any queued operations before it unlocks.
+    */
+    pub fn lock(&self) -> Option<CombiningGuard<'_, T>> {
+        self.lock.lock().map(|guard| CombiningGuard { lock: self, guard: ManuallyDrop::new(guard) })
+    }
+
+    /**
+    Runs `operation` on the data: now, if the lock is available, or else later, by the current holder.
+
+    Returns true if it ran now.  Either way, this never waits.
+    */
+    pub fn apply_or_enqueue<F>(&self, operation: F) -> bool where F: FnOnce(&mut T) + Send + 'static {
+        if let Some(mut guard) = self.lock() {
+            operation(&mut guard);
+            return true;
+        }
+        let node = Box::into_raw(Box::new(Node { operation: Box::new(operation), next: ptr::null_mut() }));
+        let mut head = self.queue.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*node).next = head };
+            match self.queue.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(new) => head = new,
+            }
+        }
+        //pairs with the fence in CombiningGuard::drop: either the holder sees our node, or we see their unlock
+        core::sync::atomic::fence(Ordering::SeqCst);
+        //if the holder left before seeing our node, run it ourselves
+        if let Some(guard) = self.lock() {
+            drop(guard);
+        }
+        false
+    }
+
+    /**
+    Consumes the lock, running any queued operations and returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        let mut this = ManuallyDrop::new(self);
+        let queue = this.queue.swap(ptr::null_mut(), Ordering::Acquire);
+        run(queue, this.lock.data.get_mut());
+        //Safety: `this` is never used or dropped again, and the queue is empty
+        unsafe { ptr::read(&this.lock) }.into_inner()
+    }
+}
+
+//runs a stack of operations, oldest first
+fn run<T>(mut head: *mut Node<T>, data: &mut T) {
+    //reverse, so the oldest is first
+    let mut oldest = ptr::null_mut();
+    while !head.is_null() {
+        let next = unsafe { (*head).next };
+        unsafe { (*head).next = oldest };
+        oldest = head;
+        head = next;
+    }
+    //if an operation panics, the ones after it are dropped without running
+    let mut remaining = Remaining(oldest);
+    while !remaining.0.is_null() {
+        let node = unsafe { Box::from_raw(remaining.0) };
+        remaining.0 = node.next;
+        (node.operation)(data);
+    }
+}
+
+//frees a list of operations, oldest first, without running them
+struct Remaining<T>(*mut Node<T>);
+
+impl<T> Drop for Remaining<T> {
+    fn drop(&mut self) {
+        while !self.0.is_null() {
+            let node = unsafe { Box::from_raw(self.0) };
+            self.0 = node.next;
+        }
+    }
+}
+
+impl<T> Drop for CombiningLock<T> {
+    fn drop(&mut self) {
+        //queued operations were promised to run
+        let queue = self.queue.swap(ptr::null_mut(), Ordering::Acquire);
+        run(queue, self.lock.data.get_mut());
+    }
+}
+
+impl<T> Default for CombiningLock<T> where T: Default {
+    fn default() -> Self {
+        CombiningLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for CombiningLock<T> {
+    fn from(data: T) -> Self {
+        CombiningLock::new(data)
+    }
+}
+
+impl<T> core::fmt::Debug for CombiningLock<T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CombiningLock")
+            .field("lock", &self.lock)
+            .field("queued", &!self.queue.load(Ordering::Relaxed).is_null())
+            .finish()
+    }
+}
+
+//operations are Send, and run on whichever thread holds the lock
+unsafe impl<T: Send> Send for CombiningLock<T> {}
+unsafe impl<T: Send> Sync for CombiningLock<T> {}
+
+/**
+A guard for [CombiningLock].
+
+When dropped, runs the queued operations and unlocks.
+*/
+#[must_use]
+pub struct CombiningGuard<'a, T> {
+    lock: &'a CombiningLock<T>,
+    guard: ManuallyDrop<Guard<'a, T>>,
+}
+
+impl<'a, T> Drop for CombiningGuard<'a, T> {
+    fn drop(&mut self) {
+        loop {
+            let queue = self.lock.queue.swap(ptr::null_mut(), Ordering::Acquire);
+            //taken out, so it unlocks even if an operation panics
+            let mut guard = unsafe { ManuallyDrop::take(&mut self.guard) };
+            run(queue, &mut guard);
+            drop(guard);
+            //pairs with the fence in apply_or_enqueue
+            core::sync::atomic::fence(Ordering::SeqCst);
+            if self.lock.queue.load(Ordering::Relaxed).is_null() {
+                return;
+            }
+            //something was queued while we were unlocking; run it, unless someone else already will
+            match self.lock.lock.lock() {
+                Some(guard) => self.guard = ManuallyDrop::new(guard),
+                None => return,
+            }
+        }
+    }
+}
+
+impl<'a, T> core::ops::Deref for CombiningGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for CombiningGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T> core::fmt::Debug for CombiningGuard<'a, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CombiningGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
@@ -0,0 +1,8 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Drop-in replacements for other crates' lock APIs.
+
+Each submodule mirrors another crate's names and signatures, so you can swap implementations with
+a feature flag and compare them.
+*/
+
+pub mod parking_lot;
@@ -0,0 +1,73 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! [parking_lot](https://docs.rs/parking_lot)'s `Mutex` and `RwLock`, built on this crate's locks.
+
+The types have parking_lot's names and, being `lock_api` types like parking_lot's own,
+the same methods and guards: `lock()` blocks, `try_lock()` returns an Option, and so on.
+So a crate can switch between the two by picking which `Mutex` to import with a `#[cfg(feature = ...)]`.
+
+Unlike parking_lot, blocking spins, rather than parking the thread, as in the rest of our
+`lock_api` implementations.  Compare the two under your workload before committing to either.
+*/
+
+use crate::{RawAtomicLock, RawAtomicRwLock};
+
+/**
+The raw mutex behind [Mutex], under parking_lot's name.
+*/
+pub type RawMutex = RawAtomicLock;
+/**
+The raw reader-writer lock behind [RwLock], under parking_lot's name.
+*/
+pub type RawRwLock = RawAtomicRwLock;
+
+/**
+A mutual exclusion lock, like `parking_lot::Mutex`.
+*/
+pub type Mutex<T> = lock_api::Mutex<RawMutex, T>;
+/**
+A guard for [Mutex], like `parking_lot::MutexGuard`.
+*/
+pub type MutexGuard<'a, T> = lock_api::MutexGuard<'a, RawMutex, T>;
+/**
+A [MutexGuard] mapped onto part of the data, like `parking_lot::MappedMutexGuard`.
+*/
+pub type MappedMutexGuard<'a, T> = lock_api::MappedMutexGuard<'a, RawMutex, T>;
+
+/**
+Creates a new [Mutex] in a `const` context, like `parking_lot::const_mutex`.
+*/
+pub const fn const_mutex<T>(val: T) -> Mutex<T> {
+    Mutex::const_new(RawAtomicLock::new(), val)
+}
+
+/**
+A reader-writer lock, like `parking_lot::RwLock`.
+*/
+pub type RwLock<T> = lock_api::RwLock<RawRwLock, T>;
+/**
+A read guard for [RwLock], like `parking_lot::RwLockReadGuard`.
+*/
+pub type RwLockReadGuard<'a, T> = lock_api::RwLockReadGuard<'a, RawRwLock, T>;
+/**
+A write guard for [RwLock], like `parking_lot::RwLockWriteGuard`.
+*/
+pub type RwLockWriteGuard<'a, T> = lock_api::RwLockWriteGuard<'a, RawRwLock, T>;
+/**
+An upgradable read guard for [RwLock], like `parking_lot::RwLockUpgradableReadGuard`.
+*/
+pub type RwLockUpgradableReadGuard<'a, T> = lock_api::RwLockUpgradableReadGuard<'a, RawRwLock, T>;
+/**
+An [RwLockReadGuard] mapped onto part of the data, like `parking_lot::MappedRwLockReadGuard`.
+*/
+pub type MappedRwLockReadGuard<'a, T> = lock_api::MappedRwLockReadGuard<'a, RawRwLock, T>;
+/**
+An [RwLockWriteGuard] mapped onto part of the data, like `parking_lot::MappedRwLockWriteGuard`.
+*/
+pub type MappedRwLockWriteGuard<'a, T> = lock_api::MappedRwLockWriteGuard<'a, RawRwLock, T>;
+
+/**
+Creates a new [RwLock] in a `const` context, like `parking_lot::const_rwlock`.
+*/
+pub const fn const_rwlock<T>(val: T) -> RwLock<T> {
+    RwLock::const_new(RawAtomicRwLock::new(), val)
+}
@@ -0,0 +1,129 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A condition variable for [AtomicLock]. */
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::{AtomicLock, Guard};
+use crate::parker::Parker;
+
+/**
+A condvar-like companion for [AtomicLock].
+
+[AtomicCondition::wait] releases a [Guard], parks the thread until notified, and reacquires the lock.
+
+As with any condition variable, wakeups may be spurious, so check your condition in a loop.
+*/
+#[derive(Debug)]
+pub struct AtomicCondition {
+    //bumped on every notify, waiters wait for it to change
+    epoch: AtomicU32,
+    parker: Parker,
+}
+
+impl AtomicCondition {
+    /**
+    Creates a new condition.
+    */
+    pub const fn new() -> Self {
+        AtomicCondition {
+            epoch: AtomicU32::new(0),
+            parker: Parker::new(),
+        }
+    }
+
+    /**
+    Releases the guard, waits for a notification, and reacquires the lock.
+
+    While we were waiting, other threads may have taken the lock; reacquiring spins and then yields
+    until it is available again.
+    */
+    pub fn wait<'a, T>(&self, guard: Guard<'a, T>) -> Guard<'a, T> {
+        let lock = guard.lock;
+        //read the epoch while we hold the lock, so a notify after our unlock is never missed
+        let epoch = self.epoch.load(Ordering::Acquire);
+        drop(guard);
+        self.parker.park_until(
+            || if self.epoch.load(Ordering::Acquire) != epoch { Some(()) } else { None },
+            || self.epoch.load(Ordering::Acquire) == epoch,
+        );
+        relock(lock)
+    }
+
+    /**
+    Like [AtomicCondition::wait], but gives up waiting after `timeout`.
+
+    Returns the reacquired guard, and whether we timed out rather than being notified.
+    Reacquiring the lock may take longer than `timeout`.
+    */
+    pub fn wait_timeout<'a, T>(&self, guard: Guard<'a, T>, timeout: Duration) -> (Guard<'a, T>, bool) {
+        let lock = guard.lock;
+        //as in wait
+        let epoch = self.epoch.load(Ordering::Acquire);
+        //a timeout too long to represent is as good as forever
+        let deadline = match Instant::now().checked_add(timeout) {
+            Some(deadline) => deadline,
+            None => return (self.wait(guard), false),
+        };
+        drop(guard);
+        let notified = self.parker.park_until_timeout(
+            || if self.epoch.load(Ordering::Acquire) != epoch { Some(()) } else { None },
+            || self.epoch.load(Ordering::Acquire) == epoch,
+            deadline,
+        );
+        (relock(lock), notified.is_none())
+    }
+
+    /**
+    Waits while `condition` returns true for the locked data.
+
+    `condition` is checked with the lock held, before the first wait and after every wakeup,
+    so spurious wakeups are handled for you.  Returns the guard once `condition` returns false.
+
+    To wait until a queue is non-empty: `condition.wait_while(guard, |queue| queue.is_empty())`.
+    */
+    pub fn wait_while<'a, T, F>(&self, mut guard: Guard<'a, T>, mut condition: F) -> Guard<'a, T> where F: FnMut(&mut T) -> bool {
+        while condition(&mut *guard) {
+            guard = self.wait(guard);
+        }
+        guard
+    }
+
+    /**
+    Wakes one waiting thread, if any.
+    */
+    pub fn notify_one(&self) {
+        self.epoch.fetch_add(1, Ordering::Release);
+        self.parker.unpark_one();
+    }
+
+    /**
+    Wakes every waiting thread.
+    */
+    pub fn notify_all(&self) {
+        self.epoch.fetch_add(1, Ordering::Release);
+        self.parker.unpark_all();
+    }
+}
+
+impl Default for AtomicCondition {
+    fn default() -> Self {
+        AtomicCondition::new()
+    }
+}
+
+fn relock<T>(lock: &AtomicLock<T>) -> Guard<'_, T> {
+    const SPINS: u32 = 100;
+    let mut attempts = 0;
+    loop {
+        if let Some(guard) = lock.lock() {
+            return guard;
+        }
+        if attempts < SPINS {
+            attempts += 1;
+            core::hint::spin_loop();
+        } else {
+            thread::yield_now();
+        }
+    }
+}
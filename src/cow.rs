@@ -0,0 +1,176 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A lock whose readers get a copy, rather than nothing, while it's busy. */
+
+use core::mem::ManuallyDrop;
+use crate::{AtomicLock, AtomicRwLock, Guard};
+
+/**
+A lock where readers who find it busy get a clone of the last committed value instead of failing.
+
+Writers take the lock with [CowLock::lock] as usual.  When the guard is dropped, the new value is
+committed: it's cloned into a second copy, which [CowLock::read] clones out while the lock is held.
+For configuration data and the like, a slightly stale copy beats a None.
+
+That costs a clone per write, and a clone per read that finds the lock busy.
+Committing waits for readers that are in the middle of cloning the old value.
+*/
+pub struct CowLock<T: Clone> {
+    lock: AtomicLock<T>,
+    committed: AtomicRwLock<T>,
+}
+
+impl<T: Clone> CowLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub fn new(data: T) -> Self {
+        CowLock {
+            committed: AtomicRwLock::new(data.clone()),
+            lock: AtomicLock::new(data),
+        }
+    }
+
+    /**
+    Locks the lock for writing, if available.
+    If the lock is unavailable, will return None.
+
+    Changes are committed for readers when the guard is dropped.
+    */
+    pub fn lock(&self) -> Option<CowGuard<'_, T>> {
+        self.lock.lock().map(|guard| CowGuard { lock: self, guard: ManuallyDrop::new(guard) })
+    }
+
+    /**
+    Reads the data: the live value if the lock is available, or else a clone of the last committed value.
+
+    Never returns None, and only waits for a writer that is in the middle of committing.
+    */
+    pub fn read(&self) -> CowRead<'_, T> {
+        if let Some(guard) = self.lock.lock() {
+            return CowRead(ReadInner::Live(guard));
+        }
+        loop {
+            if let Some(committed) = self.committed.try_read() {
+                return CowRead(ReadInner::Stale((*committed).clone()));
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T: Clone + Default> Default for CowLock<T> {
+    fn default() -> Self {
+        CowLock::new(T::default())
+    }
+}
+
+impl<T: Clone> From<T> for CowLock<T> {
+    fn from(data: T) -> Self {
+        CowLock::new(data)
+    }
+}
+
+impl<T: Clone + core::fmt::Debug> core::fmt::Debug for CowLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CowLock")
+            .field("data", &*self.read())
+            .finish()
+    }
+}
+
+/**
+A guard for writing to a [CowLock].
+
+Commits the new value and unlocks when dropped.
+If cloning the value panics, the lock is unlocked anyway, and readers may not see the change.
+*/
+#[must_use]
+pub struct CowGuard<'a, T: Clone> {
+    lock: &'a CowLock<T>,
+    guard: ManuallyDrop<Guard<'a, T>>,
+}
+
+impl<'a, T: Clone> Drop for CowGuard<'a, T> {
+    fn drop(&mut self) {
+        //taken out first, so the lock is unlocked even if cloning panics
+        let guard = unsafe { ManuallyDrop::take(&mut self.guard) };
+        //readers only hold this long enough to clone
+        let mut committed = loop {
+            if let Some(committed) = self.lock.committed.try_write() {
+                break committed;
+            }
+            core::hint::spin_loop();
+        };
+        committed.clone_from(&guard);
+        drop(committed);
+        drop(guard);
+    }
+}
+
+impl<'a, T: Clone> core::ops::Deref for CowGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T: Clone> core::ops::DerefMut for CowGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T: Clone + core::fmt::Debug> core::fmt::Debug for CowGuard<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CowGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+/**
+The result of [CowLock::read]: either the live value, with the lock held, or a clone.
+*/
+pub struct CowRead<'a, T>(ReadInner<'a, T>);
+
+enum ReadInner<'a, T> {
+    Live(Guard<'a, T>),
+    Stale(T),
+}
+
+impl<'a, T> CowRead<'a, T> {
+    /**
+    Whether this is a clone of the last committed value, rather than the live value.
+
+    A live read holds the lock until it's dropped.
+    */
+    pub fn is_stale(&self) -> bool {
+        matches!(self.0, ReadInner::Stale(_))
+    }
+}
+
+impl<'a, T> core::ops::Deref for CowRead<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        match &self.0 {
+            ReadInner::Live(guard) => guard,
+            ReadInner::Stale(data) => data,
+        }
+    }
+}
+
+impl<'a, T: core::fmt::Debug> core::fmt::Debug for CowRead<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CowRead")
+            .field("stale", &self.is_stale())
+            .field("data", &**self)
+            .finish()
+    }
+}
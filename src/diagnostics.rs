@@ -0,0 +1,145 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Human-readable reports of lock state.
+
+Reports are formatted lazily through [Display], without allocating or taking the lock,
+so they can be written from a signal handler or a crash reporter.  The exception is the
+`holder-backtrace` feature, whose holder records are cloned and formatted into the report.
+*/
+
+use core::fmt::{Display, Formatter};
+use crate::{AtomicLock, StateWord};
+
+/**
+A human-readable report on a lock, from e.g. [AtomicLock::diagnostics_report].
+
+Format it with `{}`.  The exact text is for humans, and may change between versions.
+*/
+#[derive(Debug)]
+pub struct DiagnosticsReport<'a, L: ?Sized> {
+    lock: &'a L,
+}
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    Returns a report of the lock's state, suitable for logs and crash reports.
+
+    Doesn't take the lock, so it works even while you hold it, or when the lock is stuck.
+    */
+    pub fn diagnostics_report(&self) -> DiagnosticsReport<'_, Self> {
+        DiagnosticsReport { lock: self }
+    }
+}
+
+impl<'a, T, S: StateWord> Display for DiagnosticsReport<'a, AtomicLock<T, S>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let state = self.lock.lock.bits();
+        let locked = state & crate::LOCKED as u64 != 0;
+        write!(f, "AtomicLock<{}> at {:p}: ", core::any::type_name::<T>(), self.lock)?;
+        if locked {
+            f.write_str("locked")?;
+        } else {
+            f.write_str("unlocked")?;
+        }
+        //one digit per bit of the word, plus the 0b
+        let width = 2 + 8 * core::mem::size_of::<S>();
+        write!(f, " (state {:#0width$b})", state, width = width)?;
+        #[cfg(feature = "holder-backtrace")]
+        if locked {
+            match crate::holder::try_holder(self.lock as *const AtomicLock<T, S> as *const ()) {
+                Some(Some(holder)) => write!(f, "\n{}", holder)?,
+                Some(None) => {}
+                None => f.write_str("\nholder unknown: the holder table is busy")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Identifies a lock, for correlating events about it in external tools.
+
+Derived from the lock's address, so it's the same for as long as the lock doesn't move,
+and matches the address the `profiling` hooks are given.  Once a lock is dropped,
+another lock may get its id.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LockId(usize);
+
+impl LockId {
+    /**
+    The id as a number, for exporting.
+    */
+    pub fn as_usize(self) -> usize {
+        self.0
+    }
+
+    #[cfg(feature = "holder-backtrace")]
+    pub(crate) fn from_address(address: usize) -> Self {
+        LockId(address)
+    }
+}
+
+impl Display for LockId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    An id for this lock, for lock-order analyzers, contention dashboards and the like.
+    */
+    pub fn lock_id(&self) -> LockId {
+        LockId(self as *const Self as *const () as usize)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> crate::LeakCheckedLock<T> {
+    /**
+    Returns a report of the lock's state and generation, suitable for logs and crash reports.
+
+    A lock that's held with the same generation in two reports far enough apart was likely
+    leaked; see [LeakCheckedLock::is_likely_leaked](crate::LeakCheckedLock::is_likely_leaked).
+    */
+    pub fn diagnostics_report(&self) -> DiagnosticsReport<'_, Self> {
+        DiagnosticsReport { lock: self }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<'a, T> Display for DiagnosticsReport<'a, crate::LeakCheckedLock<T>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "LeakCheckedLock<{}> at {:p}: ", core::any::type_name::<T>(), self.lock)?;
+        if self.lock.is_locked() {
+            f.write_str("locked")?;
+        } else {
+            f.write_str("unlocked")?;
+        }
+        write!(f, " (generation {})", self.lock.generation())
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<T> crate::SharedMemoryLock<T> {
+    /**
+    Returns a report of the lock's state and holder, suitable for logs and crash reports.
+
+    Doesn't take the lock, so it works even while you hold it, or when the lock is stuck.
+    */
+    pub fn diagnostics_report(&self) -> DiagnosticsReport<'_, Self> {
+        DiagnosticsReport { lock: self }
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<'a, T> Display for DiagnosticsReport<'a, crate::SharedMemoryLock<T>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SharedMemoryLock<{}> at {:p}: ", core::any::type_name::<T>(), self.lock)?;
+        match self.lock.owner() {
+            Some(pid) => write!(f, "held by pid {}", pid)?,
+            None => f.write_str("unlocked")?,
+        }
+        write!(f, " (generation {})", self.lock.generation())
+    }
+}
@@ -0,0 +1,200 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Double buffering: readers see one buffer while a writer fills the other. */
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+Two buffers: readers see the front one, while a writer mutates the back one, then publishes it
+by flipping which is which.
+
+Readers never wait for writers.  A writer can't lock the back buffer while a reader from before
+the last flip is still reading it; [DoubleBuffered::lock] returns None then.
+This suits frame data shared between a simulation thread and a render thread.
+*/
+pub struct DoubleBuffered<T> {
+    buffers: [UnsafeCell<T>; 2],
+    readers: [AtomicUsize; 2],
+    front: AtomicUsize,
+    writer: AtomicLock<()>,
+}
+
+impl<T> DoubleBuffered<T> {
+    /**
+    Creates new buffers.  `front` is visible to readers, and `back` will be written first.
+    */
+    pub const fn from_buffers(front: T, back: T) -> Self {
+        DoubleBuffered {
+            buffers: [UnsafeCell::new(front), UnsafeCell::new(back)],
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            front: AtomicUsize::new(0),
+            writer: AtomicLock::new(()),
+        }
+    }
+
+    /**
+    Reads the front buffer.  Never waits for a writer.
+
+    # Panics
+    If the reader count would overflow, which takes leaking guards.
+    */
+    pub fn read(&self) -> FrontGuard<'_, T> {
+        loop {
+            let front = self.front.load(Ordering::SeqCst);
+            let readers = &self.readers[front];
+            let mut count = readers.load(Ordering::Relaxed);
+            loop {
+                //wrapping around would let a writer in with us
+                let new = count.checked_add(1).expect("too many readers");
+                match readers.compare_exchange_weak(count, new, Ordering::SeqCst, Ordering::Relaxed) {
+                    Ok(_) => break,
+                    Err(now) => count = now,
+                }
+            }
+            //if a writer flipped in between, it may not have seen us; try the new front instead
+            if self.front.load(Ordering::SeqCst) == front {
+                return FrontGuard { buffers: self, index: front };
+            }
+            self.readers[front].fetch_sub(1, Ordering::Release);
+        }
+    }
+
+    /**
+    Locks the back buffer for writing, if available.
+
+    Returns None if another writer holds it, or a reader from before the last flip is still reading it.
+    */
+    pub fn lock(&self) -> Option<BackGuard<'_, T>> {
+        let writer = self.writer.lock()?;
+        //only writers flip, so this stays the back while we hold the writer lock
+        let back = 1 - self.front.load(Ordering::SeqCst);
+        //pairs with the loads in read: a reader we don't count here rechecks front and backs off
+        if self.readers[back].load(Ordering::SeqCst) != 0 {
+            return None;
+        }
+        Some(BackGuard { buffers: self, index: back, _writer: writer })
+    }
+
+    /**
+    Consumes the buffers, returning (front, back).
+    */
+    pub fn into_inner(self) -> (T, T) {
+        let [a, b] = self.buffers;
+        if self.front.into_inner() == 0 {
+            (a.into_inner(), b.into_inner())
+        } else {
+            (b.into_inner(), a.into_inner())
+        }
+    }
+}
+
+impl<T: Clone> DoubleBuffered<T> {
+    /**
+    Creates new buffers, both starting as `data`.
+    */
+    pub fn new(data: T) -> Self {
+        DoubleBuffered::from_buffers(data.clone(), data)
+    }
+}
+
+impl<T: Default> Default for DoubleBuffered<T> {
+    fn default() -> Self {
+        DoubleBuffered::from_buffers(T::default(), T::default())
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for DoubleBuffered<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DoubleBuffered")
+            .field("front", &*self.read())
+            .finish_non_exhaustive()
+    }
+}
+
+//readers share the front across threads, and writers may be on another thread from the one that drops us
+unsafe impl<T: Send> Send for DoubleBuffered<T> {}
+unsafe impl<T: Send + Sync> Sync for DoubleBuffered<T> {}
+
+/**
+A reader's view of the front buffer of a [DoubleBuffered].
+
+Keeps the buffer from being written until dropped, even if it's flipped to the back.
+*/
+#[must_use]
+pub struct FrontGuard<'a, T> {
+    buffers: &'a DoubleBuffered<T>,
+    index: usize,
+}
+
+impl<'a, T> Drop for FrontGuard<'a, T> {
+    fn drop(&mut self) {
+        //release, so the writer who sees us gone sees our reads as done
+        self.buffers.readers[self.index].fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl<'a, T> core::ops::Deref for FrontGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.buffers.buffers[self.index].get() }
+    }
+}
+
+impl<'a, T: core::fmt::Debug> core::fmt::Debug for FrontGuard<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FrontGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+/**
+A writer's lock on the back buffer of a [DoubleBuffered].
+
+[BackGuard::publish] flips it to the front.  Dropping it without publishing keeps the changes
+in the back buffer, for the next writer.
+*/
+#[must_use]
+pub struct BackGuard<'a, T> {
+    buffers: &'a DoubleBuffered<T>,
+    index: usize,
+    _writer: Guard<'a, ()>,
+}
+
+impl<'a, T> BackGuard<'a, T> {
+    /**
+    The front buffer, which readers currently see; e.g. to copy it before changing the back.
+    */
+    pub fn front(&self) -> &T {
+        unsafe { &*self.buffers.buffers[1 - self.index].get() }
+    }
+
+    /**
+    Flips the back buffer to the front, so readers see it from now on.
+    */
+    pub fn publish(self) {
+        self.buffers.front.store(self.index, Ordering::SeqCst);
+    }
+}
+
+impl<'a, T> core::ops::Deref for BackGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.buffers.buffers[self.index].get() }
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for BackGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.buffers.buffers[self.index].get() }
+    }
+}
+
+impl<'a, T: core::fmt::Debug> core::fmt::Debug for BackGuard<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BackGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
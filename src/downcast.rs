@@ -0,0 +1,29 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locking type-erased data as its concrete type. */
+
+use alloc::boxed::Box;
+use core::any::Any;
+use crate::{AtomicLock, Guard, MappedGuard};
+
+macro_rules! downcast {
+    ($($any:ty),*) => {
+        $(
+            impl AtomicLock<Box<$any>> {
+                /**
+                Locks the lock and accesses the data as a `U`, if available and of that type.
+
+                If the lock is unavailable, or the data isn't a `U`, the lock is left unlocked and this returns None.
+                */
+                pub fn try_lock_downcast<U: Any>(&self) -> Option<MappedGuard<'_, Box<$any>, U>> {
+                    let guard = self.lock()?;
+                    if !guard.is::<U>() {
+                        return None;
+                    }
+                    Some(Guard::map(guard, |data| data.downcast_mut::<U>().expect("checked the type above")))
+                }
+            }
+        )*
+    };
+}
+
+downcast!(dyn Any, dyn Any + Send, dyn Any + Send + Sync);
@@ -0,0 +1,241 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Hardware lock elision with Intel RTM.
+
+With elision, [ElisionLock::lock] starts a hardware transaction and only *reads* the lock flag.
+The critical section then runs speculatively, and commits when the guard is dropped.
+If another thread touches the same memory, takes the flag, or the transaction runs into anything
+RTM can't handle (syscalls, I/O, too much memory), the CPU rolls everything back and we take the
+flag the ordinary way.
+
+Read-dominated critical sections scale because readers never write the shared cache line.
+*/
+
+use core::arch::asm;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU8, Ordering};
+use crate::AtomicLock;
+
+/**
+An [AtomicLock] that tries to elide the lock with a hardware transaction first.
+
+On CPUs without RTM (or where it is disabled by microcode), this behaves exactly like [AtomicLock].
+
+Keep critical sections small and free of syscalls, or every elision attempt will abort and you pay
+for the transaction on top of the lock.
+*/
+#[derive(Debug, Default)]
+pub struct ElisionLock<T> {
+    lock: AtomicLock<T>,
+}
+
+impl<T> ElisionLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        ElisionLock { lock: AtomicLock::new(data) }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+
+    Tries a hardware transaction first, then falls back to the lock flag.
+    */
+    pub fn lock(&self) -> Option<ElisionGuard<'_, T>> {
+        if rtm_available() {
+            //Safety: we checked for RTM support.
+            if unsafe { xtest() } {
+                //Already eliding something, which might be this very lock.  We can't tell, and handing out
+                //a second guard to the same data would be unsound, so we abort to the outermost
+                //lock call, which then takes its flag.
+                //Safety: we are inside a transaction
+                unsafe { xabort() };
+            }
+            //Safety: we checked for RTM support.
+            if unsafe { xbegin() } == XBEGIN_STARTED {
+                //reading the flag puts it in our read set, so a thread that takes it will abort us
+                if !self.lock.is_locked() {
+                    return Some(ElisionGuard { lock: self, state: State::Elided, _not_send: PhantomData });
+                }
+                //Safety: we are inside a transaction
+                unsafe { xabort() };
+            }
+        }
+        self.lock.lock().map(|guard| {
+            core::mem::forget(guard);
+            ElisionGuard { lock: self, state: State::Locked, _not_send: PhantomData }
+        })
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T> From<T> for ElisionLock<T> {
+    fn from(data: T) -> Self {
+        ElisionLock::new(data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Elided,
+    Locked,
+}
+
+/**
+A guard for [ElisionLock].
+
+Commits the transaction, or unlocks, when dropped.
+
+Unlike [Guard](crate::Guard), this is not `Send`: a transaction has to end on the thread that started it.
+*/
+#[must_use]
+pub struct ElisionGuard<'a, T> {
+    lock: &'a ElisionLock<T>,
+    state: State,
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl<'a, T> ElisionGuard<'a, T> {
+    /**
+    Whether this guard is running inside a hardware transaction, rather than holding the lock flag.
+    */
+    pub fn is_elided(&self) -> bool {
+        self.state == State::Elided
+    }
+}
+
+impl<'a, T> Drop for ElisionGuard<'a, T> {
+    fn drop(&mut self) {
+        match self.state {
+            //Safety: an elided guard only exists inside its transaction; if it aborted, we never got here.
+            State::Elided => unsafe { xend() },
+            State::Locked => self.lock.lock.unlock(),
+        }
+    }
+}
+
+impl<'a, T> core::fmt::Debug for ElisionGuard<'a, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ElisionGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> core::ops::Deref for ElisionGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.lock.data.get() }
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for ElisionGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.lock.data.get() }
+    }
+}
+
+impl<'a, T> AsRef<T> for ElisionGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> AsMut<T> for ElisionGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T> core::fmt::Display for ElisionGuard<'a, T> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+const XBEGIN_STARTED: u32 = !0;
+
+const UNKNOWN: u8 = 0;
+const AVAILABLE: u8 = 1;
+const UNAVAILABLE: u8 = 2;
+static RTM: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+fn rtm_available() -> bool {
+    match RTM.load(Ordering::Relaxed) {
+        AVAILABLE => true,
+        UNAVAILABLE => false,
+        _ => {
+            let available = detect_rtm();
+            RTM.store(if available { AVAILABLE } else { UNAVAILABLE }, Ordering::Relaxed);
+            available
+        }
+    }
+}
+
+fn detect_rtm() -> bool {
+    use core::arch::x86_64::{__cpuid, __cpuid_count};
+    //Safety: cpuid is available on every x86_64 CPU.  Newer compilers consider it safe anyway.
+    #[allow(unused_unsafe)]
+    unsafe {
+        if __cpuid(0).eax < 7 {
+            return false;
+        }
+        let leaf7 = __cpuid_count(7, 0);
+        let rtm = leaf7.ebx & (1 << 11) != 0;
+        //newer microcode keeps the RTM bit but makes every transaction abort
+        let always_abort = leaf7.edx & (1 << 11) != 0;
+        rtm && !always_abort
+    }
+}
+
+/**
+Starts a transaction.  Returns [XBEGIN_STARTED], or the abort status once the transaction has been rolled back.
+
+# Safety
+RTM must be available.
+*/
+#[inline(always)]
+unsafe fn xbegin() -> u32 {
+    let status: u32;
+    asm!("xbegin 2f", "2:", inout("eax") XBEGIN_STARTED => status, options(nostack));
+    status
+}
+
+/**
+Whether we are inside a transaction.
+
+# Safety
+RTM must be available.
+*/
+#[inline(always)]
+unsafe fn xtest() -> bool {
+    let in_transaction: u8;
+    //xtest clears ZF when inside a transaction
+    asm!("xtest", "setnz {}", out(reg_byte) in_transaction, options(nostack, nomem));
+    in_transaction != 0
+}
+
+/**
+# Safety
+Must be inside a transaction.
+*/
+#[inline(always)]
+unsafe fn xend() {
+    asm!("xend", options(nostack));
+}
+
+/**
+# Safety
+Must be inside a transaction.
+*/
+#[inline(always)]
+unsafe fn xabort() {
+    asm!("xabort 0xff", options(nostack));
+}
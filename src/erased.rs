@@ -0,0 +1,78 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Guards with the data type erased, for holding many different locks at once. */
+
+use crate::state::State;
+use crate::{Guard, MappedGuard, StateWord};
+
+/**
+A guard for some [AtomicLock](crate::AtomicLock), whatever its data type.
+
+Erasing a guard gives up access to the data, but keeps the lock held until the erased guard
+is dropped.  Guards for different `AtomicLock<T>` can then go in one collection, and be released
+together, e.g. at the end of a scope that needed several locks.
+
+This doesn't allocate: it only keeps a reference to the lock's state.  Only the state word
+type `S` is kept, so locks sharing it erase to the same type.
+*/
+#[must_use]
+pub struct ErasedGuard<'a, S: StateWord = State> {
+    lock: &'a S,
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl<'a> ErasedGuard<'a> {
+    /**
+    Locks the lock whose state is `lock`, if available, for callers that kept only the state.
+    */
+    pub(crate) fn try_lock(lock: &'a State) -> Option<Self> {
+        use crate::state::sealed::Word;
+        if lock.try_acquire() {
+            //the state is first in our repr(C) layout, so this is the lock's address too
+            #[cfg(feature = "profiling")]
+            crate::profiling::hold_begin(lock as *const State as *const ());
+            Some(ErasedGuard { lock })
+        } else {
+            #[cfg(feature = "thread-stats")]
+            crate::thread_stats::failed();
+            None
+        }
+    }
+
+    pub(crate) fn state(&self) -> &'a State {
+        self.lock
+    }
+}
+
+impl<'a, S: StateWord> ErasedGuard<'a, S> {
+    /**
+    Unlocks the lock.  The same as dropping the guard.
+    */
+    pub fn unlock(self) {}
+}
+
+impl<'a, S: StateWord> Drop for ErasedGuard<'a, S> {
+    #[inline]
+    fn drop(&mut self) {
+        crate::unlock(self.lock);
+    }
+}
+
+impl<'a, T, S: StateWord> From<Guard<'a, T, S>> for ErasedGuard<'a, S> {
+    fn from(guard: Guard<'a, T, S>) -> Self {
+        let lock = &guard.lock.lock;
+        core::mem::forget(guard);
+        ErasedGuard { lock }
+    }
+}
+
+impl<'a, T, U, S: StateWord> From<MappedGuard<'a, T, U, S>> for ErasedGuard<'a, S> {
+    fn from(guard: MappedGuard<'a, T, U, S>) -> Self {
+        ErasedGuard { lock: &MappedGuard::into_lock(guard).lock }
+    }
+}
+
+impl<'a, S: StateWord> core::fmt::Debug for ErasedGuard<'a, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ErasedGuard").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,179 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Native wait-on-address primitives.
+
+* Linux: `futex`
+* Windows: `WaitOnAddress`
+* macOS: `os_sync_wait_on_address`, which requires macOS 14.4 or later.
+* wasm32 with the `atomics` target feature: `memory.atomic.wait32`.  Browsers don't allow waiting on the
+  main thread, so only block from web workers.
+
+All of these may wake spuriously, so callers must re-check their condition in a loop.
+*/
+
+use core::sync::atomic::AtomicU32;
+use core::time::Duration;
+
+//AtomicU32 has the same in-memory representation as u32
+fn addr(word: &AtomicU32) -> *mut u32 {
+    word as *const AtomicU32 as *mut u32
+}
+
+/**
+Blocks the current thread while `word` contains `expected`.
+
+Returns immediately if `word` doesn't contain `expected`.
+*/
+#[cfg(target_os = "linux")]
+pub(crate) fn wait(word: &AtomicU32, expected: u32) {
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            addr(word),
+            libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+            expected,
+            core::ptr::null::<libc::timespec>(),
+        );
+    }
+}
+
+/**
+Like [wait], but gives up after about `timeout`.
+*/
+#[cfg(target_os = "linux")]
+pub(crate) fn wait_timeout(word: &AtomicU32, expected: u32, timeout: Duration) {
+    //a relative timeout; saturating, since a longer one is as good as forever
+    let timeout = libc::timespec {
+        tv_sec: timeout.as_secs().try_into().unwrap_or(libc::time_t::MAX),
+        //always below 1e9, so this fits even a 32-bit c_long
+        tv_nsec: timeout.subsec_nanos() as libc::c_long,
+    };
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            addr(word),
+            libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+            expected,
+            &timeout as *const libc::timespec,
+        );
+    }
+}
+
+/**
+Wakes one thread blocked in [wait] on `word`.
+*/
+#[cfg(target_os = "linux")]
+pub(crate) fn wake_one(word: &AtomicU32) {
+    unsafe {
+        libc::syscall(libc::SYS_futex, addr(word), libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG, 1);
+    }
+}
+
+
+/**
+Wakes every thread blocked in [wait] on `word`.
+*/
+#[cfg(target_os = "linux")]
+pub(crate) fn wake_all(word: &AtomicU32) {
+    unsafe {
+        libc::syscall(libc::SYS_futex, addr(word), libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG, i32::MAX);
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn wait(word: &AtomicU32, expected: u32) {
+    use windows_sys::Win32::System::Threading::{WaitOnAddress, INFINITE};
+    unsafe {
+        WaitOnAddress(addr(word).cast(), &expected as *const u32 as *const _, 4, INFINITE);
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn wait_timeout(word: &AtomicU32, expected: u32, timeout: Duration) {
+    use windows_sys::Win32::System::Threading::{WaitOnAddress, INFINITE};
+    //round up, so we don't spin on sub-millisecond timeouts; INFINITE is u32::MAX, so stay below it
+    let millis = ((timeout.as_nanos() + 999_999) / 1_000_000).min((INFINITE - 1) as u128) as u32;
+    unsafe {
+        WaitOnAddress(addr(word).cast(), &expected as *const u32 as *const _, 4, millis);
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn wake_one(word: &AtomicU32) {
+    unsafe {
+        windows_sys::Win32::System::Threading::WakeByAddressSingle(addr(word).cast());
+    }
+}
+
+
+#[cfg(target_os = "windows")]
+pub(crate) fn wake_all(word: &AtomicU32) {
+    unsafe {
+        windows_sys::Win32::System::Threading::WakeByAddressAll(addr(word).cast());
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn wait(word: &AtomicU32, expected: u32) {
+    unsafe {
+        libc::os_sync_wait_on_address(addr(word).cast(), expected as u64, 4, libc::OS_SYNC_WAIT_ON_ADDRESS_NONE);
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn wait_timeout(word: &AtomicU32, expected: u32, timeout: Duration) {
+    let nanos = timeout.as_nanos().min(u64::MAX as u128) as u64;
+    unsafe {
+        libc::os_sync_wait_on_address_with_timeout(
+            addr(word).cast(),
+            expected as u64,
+            4,
+            libc::OS_SYNC_WAIT_ON_ADDRESS_NONE,
+            libc::OS_CLOCK_MACH_ABSOLUTE_TIME,
+            nanos,
+        );
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn wake_one(word: &AtomicU32) {
+    unsafe {
+        libc::os_sync_wake_by_address_any(addr(word).cast(), 4, libc::OS_SYNC_WAKE_BY_ADDRESS_NONE);
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn wake_all(word: &AtomicU32) {
+    unsafe {
+        libc::os_sync_wake_by_address_all(addr(word).cast(), 4, libc::OS_SYNC_WAKE_BY_ADDRESS_NONE);
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "atomics"))]
+pub(crate) fn wait(word: &AtomicU32, expected: u32) {
+    //a negative timeout waits forever
+    unsafe {
+        core::arch::wasm32::memory_atomic_wait32(addr(word).cast(), expected as i32, -1);
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "atomics"))]
+pub(crate) fn wait_timeout(word: &AtomicU32, expected: u32, timeout: Duration) {
+    let nanos = timeout.as_nanos().min(i64::MAX as u128) as i64;
+    unsafe {
+        core::arch::wasm32::memory_atomic_wait32(addr(word).cast(), expected as i32, nanos);
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "atomics"))]
+pub(crate) fn wake_one(word: &AtomicU32) {
+    unsafe {
+        core::arch::wasm32::memory_atomic_notify(addr(word).cast(), 1);
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "atomics"))]
+pub(crate) fn wake_all(word: &AtomicU32) {
+    unsafe {
+        core::arch::wasm32::memory_atomic_notify(addr(word).cast(), u32::MAX);
+    }
+}
@@ -0,0 +1,178 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A registry of locks that can all be taken at once. */
+
+use alloc::vec::Vec;
+use crate::state::State;
+use crate::{AtomicLock, ErasedGuard, Guard};
+
+/**
+A set of locks, of any data types, that can be locked all at once with [LockGroup::try_lock_all].
+
+For stop-the-world operations like snapshots and checkpoints: register every lock whose data
+the snapshot covers, then take them all together.  Locks are taken in address order, so two
+threads locking overlapping groups can't each end up holding what the other one needs.
+*/
+pub struct LockGroup<'a> {
+    //the members' states, sorted by address
+    members: AtomicLock<Vec<&'a State>>,
+}
+
+impl<'a> LockGroup<'a> {
+    /**
+    Creates an empty group.
+    */
+    pub const fn new() -> Self {
+        LockGroup { members: AtomicLock::new(Vec::new()) }
+    }
+
+    fn members(&self) -> Guard<'_, Vec<&'a State>> {
+        loop {
+            if let Some(guard) = self.members.lock() {
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /**
+    Adds `lock` to the group.  Returns false if it was already a member.
+    */
+    pub fn register<T>(&self, lock: &'a AtomicLock<T>) -> bool {
+        let state = &lock.lock;
+        let mut members = self.members();
+        match members.binary_search_by_key(&address(state), |member| address(member)) {
+            Ok(_) => false,
+            Err(position) => {
+                members.insert(position, state);
+                true
+            }
+        }
+    }
+
+    /**
+    Removes `lock` from the group.  Returns false if it wasn't a member.
+    */
+    pub fn unregister<T>(&self, lock: &AtomicLock<T>) -> bool {
+        let mut members = self.members();
+        match members.binary_search_by_key(&address(&lock.lock), |member| address(member)) {
+            Ok(position) => {
+                members.remove(position);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /**
+    The number of locks in the group.
+    */
+    pub fn len(&self) -> usize {
+        self.members().len()
+    }
+
+    /**
+    Whether the group has no locks.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+    Locks every lock in the group, or none of them.
+
+    If any lock is unavailable, the ones already taken are unlocked again, in reverse order,
+    and this returns None.  Registering and unregistering wait while this runs.
+    */
+    pub fn try_lock_all(&self) -> Option<GroupGuard<'a>> {
+        let members = self.members();
+        let mut guards = Vec::with_capacity(members.len());
+        for member in members.iter() {
+            match ErasedGuard::try_lock(member) {
+                Some(guard) => guards.push(guard),
+                None => {
+                    while guards.pop().is_some() {}
+                    return None;
+                }
+            }
+        }
+        Some(GroupGuard { guards })
+    }
+}
+
+fn address(state: &State) -> usize {
+    state as *const State as usize
+}
+
+impl<'a> Default for LockGroup<'a> {
+    fn default() -> Self {
+        LockGroup::new()
+    }
+}
+
+impl<'a> core::fmt::Debug for LockGroup<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.members.lock() {
+            Some(members) => f.debug_struct("LockGroup").field("len", &members.len()).finish(),
+            None => f.debug_struct("LockGroup").field("len", &format_args!("<locked>")).finish(),
+        }
+    }
+}
+
+/**
+Every lock in a [LockGroup], from [LockGroup::try_lock_all].
+
+Unlocks them all when dropped, in the reverse of the order they were taken.
+Meanwhile, [GroupGuard::get] and [GroupGuard::get_mut] access the data of any of them.
+*/
+#[must_use]
+pub struct GroupGuard<'a> {
+    guards: Vec<ErasedGuard<'a>>,
+}
+
+impl<'a> GroupGuard<'a> {
+    /**
+    The data of `lock`, if it's one of the locks held.
+    */
+    pub fn get<'g, T>(&'g self, lock: &'g AtomicLock<T>) -> Option<&'g T> {
+        //Safety: we hold the lock, and the data is borrowed from us
+        self.find(lock).map(|_| unsafe { &*lock.data.get() })
+    }
+
+    /**
+    The data of `lock`, mutably, if it's one of the locks held.
+    */
+    pub fn get_mut<'g, T>(&'g mut self, lock: &'g AtomicLock<T>) -> Option<&'g mut T> {
+        //Safety: as in get, and we're borrowed mutably, so nobody else can get it through us
+        self.find(lock).map(|_| unsafe { &mut *lock.data.get() })
+    }
+
+    fn find<T>(&self, lock: &AtomicLock<T>) -> Option<usize> {
+        self.guards.binary_search_by_key(&address(&lock.lock), |guard| address(guard.state())).ok()
+    }
+
+    /**
+    The number of locks held.
+    */
+    pub fn len(&self) -> usize {
+        self.guards.len()
+    }
+
+    /**
+    Whether no locks are held, because the group was empty.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.guards.is_empty()
+    }
+}
+
+impl<'a> Drop for GroupGuard<'a> {
+    fn drop(&mut self) {
+        while self.guards.pop().is_some() {}
+    }
+}
+
+impl<'a> core::fmt::Debug for GroupGuard<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GroupGuard").field("len", &self.guards.len()).finish()
+    }
+}
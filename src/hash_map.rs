@@ -0,0 +1,95 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A hash map split into independently locked shards. */
+
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash, Hasher};
+use std::collections::hash_map::{Entry, HashMap, RandomState};
+use crate::AtomicLock;
+use crate::padded::CachePadded;
+
+/**
+A hash map split into `SHARDS` shards, each its own [AtomicLock].
+
+Each key lives in the shard its hash picks, so operations on keys in different shards
+don't contend.  Like the lock itself, every operation is a single attempt: if the key's
+shard is busy, it returns right away, and you decide whether to retry.
+
+Each shard is padded to a cache line, so neighbouring shards don't false-share.
+*/
+#[derive(Debug)]
+pub struct LockedHashMap<K, V, const SHARDS: usize> {
+    hasher: RandomState,
+    shards: [CachePadded<AtomicLock<HashMap<K, V>>>; SHARDS],
+}
+
+impl<K, V, const SHARDS: usize> LockedHashMap<K, V, SHARDS> {
+    /**
+    Creates a new, empty map.
+
+    # Panics
+    If `SHARDS` is 0.
+    */
+    pub fn new() -> Self {
+        assert!(SHARDS > 0, "LockedHashMap needs at least one shard");
+        LockedHashMap {
+            hasher: RandomState::new(),
+            shards: core::array::from_fn(|_| CachePadded(AtomicLock::new(HashMap::new()))),
+        }
+    }
+
+    /**
+    Consumes the map, returning each shard's contents.
+    */
+    pub fn into_inner(self) -> [HashMap<K, V>; SHARDS] {
+        self.shards.map(|shard| shard.0.into_inner())
+    }
+}
+
+impl<K: Eq + Hash, V, const SHARDS: usize> LockedHashMap<K, V, SHARDS> {
+    fn shard<Q: ?Sized + Hash>(&self, key: &Q) -> &AtomicLock<HashMap<K, V>> {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        &self.shards[(hasher.finish() % SHARDS as u64) as usize]
+    }
+
+    /**
+    Returns a clone of the value for `key`.
+
+    Returns None if the key's shard is busy, and Some(None) if the key isn't in the map.
+    */
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<Option<V>> where K: Borrow<Q>, Q: ?Sized + Hash + Eq, V: Clone {
+        let shard = self.shard(key).lock()?;
+        Some(shard.get(key).cloned())
+    }
+
+    /**
+    Inserts `value` for `key`, returning the previous value, if any.
+
+    If the key's shard is busy, gives the key and value back.
+    */
+    pub fn insert(&self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        match self.shard(&key).lock() {
+            Some(mut shard) => Ok(shard.insert(key, value)),
+            None => Err((key, value)),
+        }
+    }
+
+    /**
+    Runs `f` on the entry for `key`, with its shard locked.
+
+    Returns None without running `f` if the key's shard is busy.  Don't touch the map from `f`:
+    if it needs the same shard, it will always find it busy.
+    */
+    pub fn with_entry<R, F>(&self, key: K, f: F) -> Option<R> where F: FnOnce(Entry<'_, K, V>) -> R {
+        let mut shard = self.shard(&key).lock()?;
+        Some(f(shard.entry(key)))
+    }
+}
+
+//boilerplate
+
+impl<K, V, const SHARDS: usize> Default for LockedHashMap<K, V, SHARDS> {
+    fn default() -> Self {
+        LockedHashMap::new()
+    }
+}
@@ -0,0 +1,171 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Tree-structured locks with intent markers. */
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+const EXCLUSIVE: usize = 1;
+const INTENT: usize = 2;
+
+/*
+The state of one node: EXCLUSIVE if the node itself is locked, plus INTENT per lock held below it.
+ */
+#[derive(Debug)]
+struct Node<'p> {
+    state: AtomicUsize,
+    parent: Option<&'p Node<'p>>,
+}
+
+impl<'p> Node<'p> {
+    fn ancestors(&self) -> impl Iterator<Item = &Node<'p>> {
+        core::iter::successors(self.parent, |node| node.parent)
+    }
+
+    fn try_intent(&self) -> bool {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & EXCLUSIVE != 0 {
+                return false;
+            }
+            match self.state.compare_exchange_weak(state, state + INTENT, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return true,
+                Err(new) => state = new,
+            }
+        }
+    }
+
+    fn release_intent(&self) {
+        self.state.fetch_sub(INTENT, Ordering::Release);
+    }
+
+    //releases intent on the first `count` ancestors
+    fn release_ancestors(&self, count: usize) {
+        for ancestor in self.ancestors().take(count) {
+            ancestor.release_intent();
+        }
+    }
+}
+
+/**
+A lock in a tree of locks, for coarse-or-fine locking of nested structures (document → section → paragraph).
+
+Locking a node puts an *intent* marker on each of its ancestors.  A node can only be locked while
+no ancestor is locked and nothing below it is locked.  So you can lock the whole document,
+or any number of disjoint paragraphs, but not both.
+*/
+#[derive(Debug)]
+pub struct IntentLock<'p, T> {
+    node: Node<'p>,
+    data: UnsafeCell<T>,
+}
+
+impl<T> IntentLock<'static, T> {
+    /**
+    Creates a new root lock.
+    */
+    pub const fn new(data: T) -> Self {
+        IntentLock {
+            node: Node { state: AtomicUsize::new(0), parent: None },
+            data: UnsafeCell::new(data),
+        }
+    }
+}
+
+impl<'p, T> IntentLock<'p, T> {
+    /**
+    Creates a new lock below `parent`.
+    */
+    pub fn new_child<U>(data: T, parent: &'p IntentLock<'_, U>) -> Self {
+        IntentLock {
+            node: Node { state: AtomicUsize::new(0), parent: Some(&parent.node) },
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /**
+    Locks this node if available.
+
+    Will return None if this node is locked, anything below it is locked, or any ancestor is locked.
+    */
+    pub fn try_lock(&self) -> Option<IntentGuard<'_, 'p, T>> {
+        let mut marked = 0;
+        for ancestor in self.node.ancestors() {
+            if !ancestor.try_intent() {
+                self.node.release_ancestors(marked);
+                return None;
+            }
+            marked += 1;
+        }
+        //test before test-and-set, so a held lock costs a shared read
+        let acquired = self.node.state.load(Ordering::Relaxed) == 0
+            && self.node.state.compare_exchange(0, EXCLUSIVE, Ordering::Acquire, Ordering::Relaxed).is_ok();
+        if acquired {
+            Some(IntentGuard { lock: self })
+        } else {
+            self.node.release_ancestors(marked);
+            None
+        }
+    }
+
+    /**
+    Whether this node is locked.  The answer may be stale by the time you read it.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.node.state.load(Ordering::Relaxed) & EXCLUSIVE != 0
+    }
+
+    /**
+    The number of locks currently held below this node.  The answer may be stale by the time you read it.
+    */
+    pub fn intents(&self) -> usize {
+        self.node.state.load(Ordering::Relaxed) / INTENT
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+unsafe impl<'p, T: Send> Send for IntentLock<'p, T> {}
+unsafe impl<'p, T: Send> Sync for IntentLock<'p, T> {}
+
+/**
+A guard for [IntentLock].
+
+Unlocks the node, and removes the intent markers from its ancestors, when dropped.
+*/
+#[must_use]
+pub struct IntentGuard<'a, 'p, T> {
+    lock: &'a IntentLock<'p, T>,
+}
+
+impl<'a, 'p, T> Drop for IntentGuard<'a, 'p, T> {
+    fn drop(&mut self) {
+        self.lock.node.state.fetch_and(!EXCLUSIVE, Ordering::Release);
+        self.lock.node.release_ancestors(usize::MAX);
+    }
+}
+
+impl<'a, 'p, T> core::ops::Deref for IntentGuard<'a, 'p, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, 'p, T> core::ops::DerefMut for IntentGuard<'a, 'p, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, 'p, T> core::fmt::Debug for IntentGuard<'a, 'p, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IntentGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
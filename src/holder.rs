@@ -0,0 +1,134 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Who holds each lock, and from where.
+
+With the `holder-backtrace` feature, every acquisition of an [AtomicLock] captures a backtrace
+and records it along with the thread, until the lock is released.  [AtomicLock::holder] and
+[held_locks] read those records, and diagnostics reports include them, so a stuck lock can be
+traced to the code holding it without attaching a debugger.
+
+Capturing a backtrace on every acquisition is slow, so this is meant for debug builds and
+for reproducing an incident, rather than for running all the time.
+
+This covers every [AtomicLock], including those inside other types in this crate,
+at the same points the `profiling` feature reports.
+*/
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::time::Duration;
+use std::backtrace::Backtrace;
+use std::thread::{self, ThreadId};
+use std::time::Instant;
+use crate::{AtomicLock, LockId, StateWord};
+
+/**
+The thread holding a lock, and where it took the lock, from [AtomicLock::holder] or [held_locks].
+*/
+#[derive(Debug, Clone)]
+pub struct LockHolder {
+    thread: ThreadId,
+    name: Option<Arc<str>>,
+    backtrace: Arc<Backtrace>,
+    taken: Instant,
+}
+
+impl LockHolder {
+    /**
+    The thread that took the lock.  If the guard was sent elsewhere, it may not be the one holding it now.
+    */
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread
+    }
+
+    /**
+    The thread's name, if it has one.
+    */
+    pub fn thread_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /**
+    The backtrace of the acquisition.
+    */
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+
+    /**
+    How long the lock has been held, so far.
+    */
+    pub fn held_for(&self) -> Duration {
+        self.taken.elapsed()
+    }
+}
+
+impl Display for LockHolder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "held by thread {:?} ({:?}), taken at:\n{}", name, self.thread, self.backtrace),
+            None => write!(f, "held by thread {:?}, taken at:\n{}", self.thread, self.backtrace),
+        }
+    }
+}
+
+//every held lock, by address
+static HOLDERS: AtomicLock<Vec<(usize, LockHolder)>> = AtomicLock::new(Vec::new());
+
+fn holders() -> crate::Guard<'static, Vec<(usize, LockHolder)>> {
+    //acquire doesn't report the hold, so recording from hold_begin doesn't record itself
+    loop {
+        if let Some(guard) = HOLDERS.acquire() {
+            return guard;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+pub(crate) fn hold_begin(lock: *const ()) {
+    //capture before taking the table, so other threads don't wait for us to walk the stack
+    let current = thread::current();
+    let holder = LockHolder {
+        thread: current.id(),
+        name: current.name().map(Into::into),
+        backtrace: Arc::new(Backtrace::force_capture()),
+        taken: Instant::now(),
+    };
+    holders().push((lock as usize, holder));
+}
+
+pub(crate) fn hold_end(lock: *const ()) {
+    //unlocking the table reports it too, while it's still held, and it was never recorded
+    if lock == &HOLDERS as *const AtomicLock<_> as *const () {
+        return;
+    }
+    let holder = {
+        let mut holders = holders();
+        holders.iter().rposition(|(held, _)| *held == lock as usize).map(|position| holders.swap_remove(position))
+    };
+    //freeing the backtrace can take a while too
+    drop(holder);
+}
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    The thread holding the lock, and the backtrace of where it took it, if the lock is held.
+    */
+    pub fn holder(&self) -> Option<LockHolder> {
+        let address = self as *const Self as usize;
+        holders().iter().rev().find(|(held, _)| *held == address).map(|(_, holder)| holder.clone())
+    }
+}
+
+/**
+Every lock that's held right now, and who holds it.
+*/
+pub fn held_locks() -> Vec<(LockId, LockHolder)> {
+    holders().iter().map(|(lock, holder)| (LockId::from_address(*lock), holder.clone())).collect()
+}
+
+//for diagnostics reports, which mustn't wait
+pub(crate) fn try_holder(lock: *const ()) -> Option<Option<LockHolder>> {
+    let holders = HOLDERS.acquire()?;
+    Some(holders.iter().rev().find(|(held, _)| *held == lock as usize).map(|(_, holder)| holder.clone()))
+}
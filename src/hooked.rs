@@ -0,0 +1,103 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Running a callback after each unlock. */
+
+use core::mem::ManuallyDrop;
+use crate::{AtomicLock, Guard};
+
+/**
+An [AtomicLock] that calls `on_unlock` after each unlock.
+
+Wrappers can use this for wake-ups, notifications or dirty flags, without having to track
+releases themselves.  The callback runs after the lock is released, so it may lock again,
+but it may also race with the next holder.
+*/
+#[derive(Debug, Default)]
+pub struct HookedLock<T, F> {
+    lock: AtomicLock<T>,
+    on_unlock: F,
+}
+
+impl<T, F: Fn()> HookedLock<T, F> {
+    /**
+    Creates a new lock, which calls `on_unlock` after each unlock.
+    */
+    pub const fn new(data: T, on_unlock: F) -> Self {
+        HookedLock {
+            lock: AtomicLock::new(data),
+            on_unlock,
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<HookedGuard<'_, T, F>> {
+        self.lock.lock().map(|guard| HookedGuard { guard: ManuallyDrop::new(guard), on_unlock: &self.on_unlock })
+    }
+
+    /**
+    Consumes the lock, returning the inner data.  Doesn't call the callback.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+/**
+A guard for [HookedLock].
+
+Unlocks and then calls the lock's callback when dropped.
+*/
+#[must_use]
+pub struct HookedGuard<'a, T, F: Fn()> {
+    guard: ManuallyDrop<Guard<'a, T>>,
+    on_unlock: &'a F,
+}
+
+impl<'a, T, F: Fn()> Drop for HookedGuard<'a, T, F> {
+    fn drop(&mut self) {
+        //unlock first, so the callback sees the lock available
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        (self.on_unlock)();
+    }
+}
+
+impl<'a, T, F: Fn()> core::ops::Deref for HookedGuard<'a, T, F> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T, F: Fn()> core::ops::DerefMut for HookedGuard<'a, T, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T, F: Fn()> AsRef<T> for HookedGuard<'a, T, F> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T, F: Fn()> AsMut<T> for HookedGuard<'a, T, F> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T, F: Fn()> core::fmt::Debug for HookedGuard<'a, T, F> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HookedGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T, F: Fn()> core::fmt::Display for HookedGuard<'a, T, F> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,325 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A blocking lock that spins, then yields, then parks. */
+
+use core::mem::ManuallyDrop;
+use core::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use crate::{AtomicLock, Guard};
+use crate::parker::Parker;
+
+/**
+How long a [HybridLock] tries each strategy before escalating to the next one.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HybridPolicy {
+    ///Number of attempts with a spin hint between them.  When `adaptive`, the most it will spin.
+    pub spins: u32,
+    ///Number of attempts with a [thread::yield_now] between them, after spinning.
+    pub yields: u32,
+    /**
+    Whether to adjust the spin count to how long recent acquisitions spun, like glibc's adaptive mutexes.
+
+    Spinning that succeeds moves the budget towards what it took, and spinning that fails shrinks it,
+    so short critical sections get spun for and long ones go to yielding and parking sooner.
+    */
+    pub adaptive: bool,
+    /**
+    Whether guards unlock with [HybridGuard::unlock_fair] when dropped.
+    */
+    pub fair: bool,
+}
+
+impl HybridPolicy {
+    /**
+    The policy used by [HybridLock::new].
+    */
+    pub const DEFAULT: HybridPolicy = HybridPolicy { spins: 100, yields: 10, adaptive: false, fair: false };
+
+    /**
+    An adaptive policy, spinning at most `max_spins` times.
+    */
+    pub const fn adaptive(max_spins: u32) -> HybridPolicy {
+        HybridPolicy { spins: max_spins, yields: 10, adaptive: true, fair: false }
+    }
+}
+
+impl Default for HybridPolicy {
+    fn default() -> Self {
+        HybridPolicy::DEFAULT
+    }
+}
+
+/**
+A lock whose [HybridLock::lock] blocks, escalating from spinning to yielding to parking the thread.
+
+Short critical sections are usually over before the spinning is, so uncontended and lightly-contended
+acquisition never touches the OS.  Long waits end up parked and consume no CPU.
+
+`MAX_SPINS` and `MAX_YIELDS` cap the policy's `spins` and `yields` at compile time.  With small caps,
+e.g. `HybridLock<T, 4, 0>` from [HybridLock::capped], the compiler knows how short the loops are,
+and can unroll them, without any runtime configuration.  By default they don't cap anything.
+*/
+#[derive(Debug)]
+pub struct HybridLock<T, const MAX_SPINS: u32 = { u32::MAX }, const MAX_YIELDS: u32 = { u32::MAX }> {
+    lock: AtomicLock<T>,
+    policy: HybridPolicy,
+    //for adaptive policies, a moving average of how many spins acquisition took, times 8
+    spin_estimate: AtomicU32,
+    //the number of fair unlocks that left the lock held for a parked thread to take over, shifted left,
+    //with HANDOFF set until one does
+    handoff: AtomicU32,
+    parker: Parker,
+}
+
+//in HybridLock::handoff, a handoff is waiting to be taken
+const HANDOFF: u32 = 1;
+
+//an adaptive lock spins this much more than it expects to need, so it notices when spinning starts working again
+const EXTRA_ADAPTIVE_SPINS: u32 = 10;
+
+impl<T> HybridLock<T> {
+    /**
+    Creates a new lock with [HybridPolicy::DEFAULT].
+    */
+    pub const fn new(data: T) -> Self {
+        HybridLock::with_policy(data, HybridPolicy::DEFAULT)
+    }
+
+    /**
+    Creates a new lock with the given policy.
+    */
+    pub const fn with_policy(data: T, policy: HybridPolicy) -> Self {
+        HybridLock::capped(data, policy)
+    }
+}
+
+impl<T, const MAX_SPINS: u32, const MAX_YIELDS: u32> HybridLock<T, MAX_SPINS, MAX_YIELDS> {
+    /**
+    Creates a new lock with the given policy, capped at `MAX_SPINS` and `MAX_YIELDS`.
+
+    For instance, `HybridLock::<T, 4, 0>::capped(data, HybridPolicy::DEFAULT)` spins 4 times, then parks.
+    */
+    pub const fn capped(data: T, policy: HybridPolicy) -> Self {
+        HybridLock {
+            lock: AtomicLock::new(data),
+            policy,
+            spin_estimate: AtomicU32::new(policy.spins.saturating_mul(4)),
+            handoff: AtomicU32::new(0),
+            parker: Parker::new(),
+        }
+    }
+
+    /**
+    The policy this lock was created with.
+    */
+    pub fn policy(&self) -> HybridPolicy {
+        self.policy
+    }
+
+    /**
+    How many times [HybridLock::lock] currently spins before yielding.
+
+    This is the policy's `spins`, unless it's adaptive, and never more than `MAX_SPINS`.
+    */
+    pub fn spin_budget(&self) -> u32 {
+        let spins = self.policy.spins.min(MAX_SPINS);
+        if !self.policy.adaptive {
+            return spins;
+        }
+        let estimate = self.spin_estimate.load(Ordering::Relaxed) / 8;
+        estimate.saturating_mul(2).saturating_add(EXTRA_ADAPTIVE_SPINS).min(spins)
+    }
+
+    //moves the estimate an eighth of the way towards `spins`; races just lose an update
+    fn record_spins(&self, spins: u32) {
+        if self.policy.adaptive {
+            let estimate = self.spin_estimate.load(Ordering::Relaxed);
+            let estimate = (estimate - estimate / 8).saturating_add(spins);
+            self.spin_estimate.store(estimate, Ordering::Relaxed);
+        }
+    }
+
+    /**
+    Locks the lock if available, without waiting.
+    */
+    pub fn try_lock(&self) -> Option<HybridGuard<'_, T, MAX_SPINS, MAX_YIELDS>> {
+        self.lock.lock().map(|guard| self.wrap(guard))
+    }
+
+    /**
+    Locks the lock, blocking the current thread until it is available.
+    */
+    pub fn lock(&self) -> HybridGuard<'_, T, MAX_SPINS, MAX_YIELDS> {
+        if let Some(guard) = self.lock.lock() {
+            return self.wrap(guard);
+        }
+        #[cfg(feature = "profiling")]
+        let zone = crate::profiling::WaitZone::new(&self.lock);
+        let guard = self.wait();
+        #[cfg(feature = "profiling")]
+        zone.acquired();
+        self.wrap(guard)
+    }
+
+    //the slow path of lock; acquires without telling the profiler
+    fn wait(&self) -> Guard<'_, T> {
+        let budget = self.spin_budget();
+        for spins in 0..budget {
+            if let Some(guard) = self.lock.acquire() {
+                self.record_spins(spins);
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+        //spinning was wasted this time
+        self.record_spins(0);
+        for _ in 0..self.policy.yields.min(MAX_YIELDS) {
+            if let Some(guard) = self.lock.acquire() {
+                return guard;
+            }
+            thread::yield_now();
+        }
+        //read before we queue, so a handoff made before we started waiting is never ours to take,
+        //and one made after we queued always is
+        let since = self.handoff.load(Ordering::Relaxed) >> 1;
+        self.parker.park_until(|| self.take_handoff(since).or_else(|| self.lock.acquire()), || self.lock.is_locked() && self.handoff_for(since).is_none())
+    }
+
+    //the handoff state, if a handoff was made since a waiter read `since`
+    fn handoff_for(&self, since: u32) -> Option<u32> {
+        let handoff = self.handoff.load(Ordering::Relaxed);
+        if handoff & HANDOFF != 0 && handoff >> 1 != since {
+            Some(handoff)
+        } else {
+            None
+        }
+    }
+
+    //takes over a lock that a fair unlock left held for a thread already waiting since `since`
+    fn take_handoff(&self, since: u32) -> Option<Guard<'_, T>> {
+        //parked threads try this on every wakeup, so read before writing
+        let handoff = self.handoff_for(since)?;
+        //acquire pairs with the release in unlock_fair, so we see the old holder's writes
+        if self.handoff.compare_exchange(handoff, handoff & !HANDOFF, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+            Some(Guard { lock: &self.lock, data: unsafe { &mut *self.lock.data.get() } })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+
+    fn wrap<'a>(&'a self, guard: Guard<'a, T>) -> HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> {
+        HybridGuard { lock: self, guard: ManuallyDrop::new(guard) }
+    }
+}
+
+impl<T, const MAX_SPINS: u32, const MAX_YIELDS: u32> Default for HybridLock<T, MAX_SPINS, MAX_YIELDS> where T: Default {
+    fn default() -> Self {
+        HybridLock::capped(T::default(), HybridPolicy::DEFAULT)
+    }
+}
+
+impl<T> From<T> for HybridLock<T> {
+    fn from(data: T) -> Self {
+        HybridLock::new(data)
+    }
+}
+
+/**
+A guard for [HybridLock].
+
+Unlocks when dropped, waking a parked thread if there is one.
+*/
+#[must_use]
+pub struct HybridGuard<'a, T, const MAX_SPINS: u32 = { u32::MAX }, const MAX_YIELDS: u32 = { u32::MAX }> {
+    lock: &'a HybridLock<T, MAX_SPINS, MAX_YIELDS>,
+    guard: ManuallyDrop<Guard<'a, T>>,
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> {
+    /**
+    Unlocks the lock, handing it straight to a parked thread if there is one.
+
+    An ordinary unlock lets whoever is spinning, or shows up first, barge in ahead of threads
+    that have been parked for a while.  Handing off is slower, since the lock is idle until
+    the parked thread wakes, but it bounds how long a parked thread waits.
+    With our own queue, the thread that gets it is the one parked longest.
+
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    */
+    pub fn unlock_fair(guard: Self) {
+        let mut guard = ManuallyDrop::new(guard);
+        guard.release_fair();
+    }
+
+    fn release_fair(&mut self) {
+        let lock = self.lock;
+        //release, so the new holder sees our writes.  We hold the lock, so the last handoff was taken;
+        //this counts a new one, and sets HANDOFF
+        if lock.parker.unpark_handoff(|| { lock.handoff.fetch_add(2 | HANDOFF, Ordering::Release); }) {
+            //the lock stays held; it's theirs now
+            #[cfg(feature = "profiling")]
+            crate::profiling::hold_end(&lock.lock as *const AtomicLock<T> as *const ());
+        } else {
+            unsafe { ManuallyDrop::drop(&mut self.guard) };
+            lock.parker.unpark_one();
+        }
+    }
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> Drop for HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> {
+    fn drop(&mut self) {
+        if self.lock.policy.fair {
+            self.release_fair();
+            return;
+        }
+        //unlock first, so whoever we wake can actually get the lock.
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        self.lock.parker.unpark_one();
+    }
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> core::fmt::Debug for HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HybridGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> core::ops::Deref for HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> core::ops::DerefMut for HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> AsRef<T> for HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> AsMut<T> for HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T, const MAX_SPINS: u32, const MAX_YIELDS: u32> core::fmt::Display for HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
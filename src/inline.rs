@@ -0,0 +1,244 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A lock whose small payload lives inside its lock word. */
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use crate::LOCKED;
+
+//the value is in the high half; the low half is the lock state, as in StateWord
+const VALUE_SHIFT: u32 = 32;
+const STATE_MASK: u64 = (1 << VALUE_SHIFT) - 1;
+
+/**
+A type small enough to store in the spare half of an [InlineAtomicLock]'s 64-bit word.
+
+This trait is sealed: the implementations are the primitives of 32 bits or fewer.
+*/
+pub trait InlineValue: Copy + sealed::Bits {}
+
+mod sealed {
+    /**
+    Conversion to and from the stored bits.  Public but unnameable, to seal [InlineValue](super::InlineValue).
+    */
+    pub trait Bits {
+        fn to_bits(self) -> u32;
+        //only ever given bits from to_bits
+        fn from_bits(bits: u32) -> Self;
+    }
+}
+
+macro_rules! inline_value {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::Bits for $ty {
+                #[inline]
+                fn to_bits(self) -> u32 {
+                    self as u32
+                }
+                #[inline]
+                fn from_bits(bits: u32) -> Self {
+                    bits as $ty
+                }
+            }
+            impl InlineValue for $ty {}
+        )*
+    };
+}
+inline_value!(u8, u16, u32, i8, i16, i32);
+
+impl sealed::Bits for bool {
+    fn to_bits(self) -> u32 {
+        self as u32
+    }
+    fn from_bits(bits: u32) -> Self {
+        bits != 0
+    }
+}
+impl InlineValue for bool {}
+
+impl sealed::Bits for char {
+    fn to_bits(self) -> u32 {
+        self as u32
+    }
+    fn from_bits(bits: u32) -> Self {
+        //bits from to_bits are always a char
+        char::from_u32(bits).unwrap_or_default()
+    }
+}
+impl InlineValue for char {}
+
+impl sealed::Bits for f32 {
+    fn to_bits(self) -> u32 {
+        f32::to_bits(self)
+    }
+    fn from_bits(bits: u32) -> Self {
+        f32::from_bits(bits)
+    }
+}
+impl InlineValue for f32 {}
+
+/**
+A lock for a value of 32 bits or fewer, stored in the same `AtomicU64` as the lock state.
+
+Since the value is part of the lock word, [InlineAtomicLock::load] reads it with one atomic load,
+and [InlineAtomicLock::try_store] writes it with one compare-and-swap, neither taking the lock.
+[InlineAtomicLock::lock] works like [AtomicLock::lock](crate::AtomicLock::lock), except that
+the guard edits a copy, which is stored back as it unlocks.
+
+While the lock is held, `load` returns the value from before it was locked.
+*/
+pub struct InlineAtomicLock<T> {
+    word: AtomicU64,
+    _value: core::marker::PhantomData<T>,
+}
+
+impl<T: InlineValue> InlineAtomicLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub fn new(value: T) -> Self {
+        InlineAtomicLock { word: AtomicU64::new(pack(value, 0)), _value: core::marker::PhantomData }
+    }
+
+    /**
+    The value, without locking, from a single atomic load.
+    */
+    #[inline]
+    pub fn load(&self) -> T {
+        unpack(self.word.load(Ordering::Acquire))
+    }
+
+    /**
+    Replaces the value without locking, if the lock isn't held.
+
+    If it is, the holder is about to store its own value, so this returns `value` back instead.
+    */
+    pub fn try_store(&self, value: T) -> Result<(), T> {
+        let mut word = self.word.load(Ordering::Relaxed);
+        loop {
+            if word & LOCKED as u64 != 0 {
+                return Err(value);
+            }
+            match self.word.compare_exchange_weak(word, pack(value, word), Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return Ok(()),
+                Err(actual) => word = actual,
+            }
+        }
+    }
+
+    /**
+    Locks the lock and accesses a copy of the value if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<InlineGuard<'_, T>> {
+        let word = self.word.load(Ordering::Relaxed);
+        if word & LOCKED as u64 != 0 {
+            return None;
+        }
+        match self.word.compare_exchange(word, word | LOCKED as u64, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => Some(InlineGuard { lock: self, value: unpack(word) }),
+            Err(_) => None,
+        }
+    }
+
+    /**
+    Whether the lock is held.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.word.load(Ordering::Relaxed) & LOCKED as u64 != 0
+    }
+
+    /**
+    Consumes the lock, returning the value.
+    */
+    pub fn into_inner(self) -> T {
+        unpack(self.word.into_inner())
+    }
+}
+
+//the value, with the state bits of `word`
+fn pack<T: InlineValue>(value: T, word: u64) -> u64 {
+    (value.to_bits() as u64) << VALUE_SHIFT | word & STATE_MASK
+}
+
+fn unpack<T: InlineValue>(word: u64) -> T {
+    T::from_bits((word >> VALUE_SHIFT) as u32)
+}
+
+impl<T: InlineValue + core::fmt::Debug> core::fmt::Debug for InlineAtomicLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InlineAtomicLock")
+            .field("locked", &self.is_locked())
+            .field("data", &self.load())
+            .finish()
+    }
+}
+
+impl<T: InlineValue + Default> Default for InlineAtomicLock<T> {
+    fn default() -> Self {
+        InlineAtomicLock::new(T::default())
+    }
+}
+
+impl<T: InlineValue> From<T> for InlineAtomicLock<T> {
+    fn from(value: T) -> Self {
+        InlineAtomicLock::new(value)
+    }
+}
+
+/**
+A guard for [InlineAtomicLock], which derefs to a copy of the value.
+
+Stores the copy back and unlocks, in one atomic store, when dropped.
+*/
+#[must_use]
+pub struct InlineGuard<'a, T: InlineValue> {
+    lock: &'a InlineAtomicLock<T>,
+    value: T,
+}
+
+impl<'a, T: InlineValue> Drop for InlineGuard<'a, T> {
+    fn drop(&mut self) {
+        //nobody else writes the word while we hold the lock
+        let word = self.lock.word.load(Ordering::Relaxed);
+        self.lock.word.store(pack(self.value, word & !(LOCKED as u64)), Ordering::Release);
+    }
+}
+
+impl<'a, T: InlineValue> core::ops::Deref for InlineGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'a, T: InlineValue> core::ops::DerefMut for InlineGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<'a, T: InlineValue> AsRef<T> for InlineGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: InlineValue> AsMut<T> for InlineGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T: InlineValue + core::fmt::Debug> core::fmt::Debug for InlineGuard<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InlineGuard")
+            .field("data", &self.value)
+            .finish()
+    }
+}
+
+impl<'a, T: InlineValue + core::fmt::Display> core::fmt::Display for InlineGuard<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,174 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Checking an invariant at every unlock. */
+
+use core::mem::ManuallyDrop;
+use core::panic::Location;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+An [AtomicLock] that asserts an invariant on the data each time a guard is released.
+
+Install the invariant with [InvariantLock::set_invariant].  A guard that leaves the data
+violating it panics as it's dropped, naming where the lock was taken, so the violation is
+caught at the unlock that introduced it rather than much later.
+
+The check only runs in debug builds, or with the `strict` feature.  Otherwise this is a plain
+[AtomicLock], and the invariant is never called.
+*/
+pub struct InvariantLock<T> {
+    lock: AtomicLock<T>,
+    //a fn(&T) -> bool, or null
+    invariant: AtomicPtr<()>,
+}
+
+const CHECKING: bool = cfg!(any(debug_assertions, feature = "strict"));
+
+impl<T> InvariantLock<T> {
+    /**
+    Creates a new lock, without an invariant.
+    */
+    pub const fn new(data: T) -> Self {
+        InvariantLock {
+            lock: AtomicLock::new(data),
+            invariant: AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+
+    /**
+    Installs `invariant`, which must return true for the data whenever the lock is released.
+
+    It's called with the lock still held, so it sees exactly what the guard left behind.
+    It isn't checked against the data as it is now; that happens at the next unlock.
+    */
+    pub fn set_invariant(&self, invariant: fn(&T) -> bool) {
+        self.invariant.store(invariant as *mut (), Ordering::Release);
+    }
+
+    /**
+    Removes the invariant, if any.
+    */
+    pub fn clear_invariant(&self) {
+        self.invariant.store(core::ptr::null_mut(), Ordering::Release);
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    #[track_caller]
+    pub fn lock(&self) -> Option<InvariantGuard<'_, T>> {
+        let site = Location::caller();
+        self.lock.lock().map(|guard| InvariantGuard { guard: ManuallyDrop::new(guard), lock: self, site })
+    }
+
+    /**
+    Consumes the lock, returning the inner data.  Doesn't check the invariant.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+
+    fn holds(&self, data: &T) -> bool {
+        let ptr = self.invariant.load(Ordering::Acquire);
+        if ptr.is_null() {
+            return true;
+        }
+        //Safety: only ever set from a fn(&T) -> bool
+        let invariant: fn(&T) -> bool = unsafe { core::mem::transmute(ptr) };
+        invariant(data)
+    }
+}
+
+impl<T> Default for InvariantLock<T> where T: Default {
+    fn default() -> Self {
+        InvariantLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for InvariantLock<T> {
+    fn from(data: T) -> Self {
+        InvariantLock::new(data)
+    }
+}
+
+impl<T> core::fmt::Debug for InvariantLock<T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InvariantLock")
+            .field("lock", &self.lock)
+            .field("has_invariant", &!self.invariant.load(Ordering::Relaxed).is_null())
+            .finish()
+    }
+}
+
+/**
+A guard for [InvariantLock].
+
+Checks the lock's invariant, unlocks, and then panics if the invariant didn't hold.
+*/
+#[must_use]
+pub struct InvariantGuard<'a, T> {
+    guard: ManuallyDrop<Guard<'a, T>>,
+    lock: &'a InvariantLock<T>,
+    site: &'static Location<'static>,
+}
+
+impl<'a, T> Drop for InvariantGuard<'a, T> {
+    fn drop(&mut self) {
+        let holds = !CHECKING || self.lock.holds(&self.guard);
+        //unlock first, so the panic can't leave the lock held
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        if !holds {
+            violated(self.site);
+        }
+    }
+}
+
+#[cold]
+fn violated(site: &'static Location<'static>) {
+    //panicking while already unwinding would abort, which loses the original panic
+    #[cfg(feature = "std")]
+    if std::thread::panicking() {
+        return;
+    }
+    panic!("invariant violated by the guard for the lock taken at {}", site);
+}
+
+impl<'a, T> core::ops::Deref for InvariantGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for InvariantGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T> AsRef<T> for InvariantGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> AsMut<T> for InvariantGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T> core::fmt::Debug for InvariantGuard<'a, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InvariantGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> core::fmt::Display for InvariantGuard<'a, T> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
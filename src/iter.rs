@@ -0,0 +1,78 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Iterating over locked collections. */
+
+use core::mem::ManuallyDrop;
+use crate::{AtomicLock, Guard};
+
+impl<'a, T> Guard<'a, T> {
+    /**
+    Iterates over the locked collection, by reference.
+
+    The same as `(&*guard).into_iter()`.  Like [Guard::map], this is an associated function,
+    so it doesn't conflict with an `iter` method on `T`: use it as `Guard::iter(&guard)`.
+    */
+    pub fn iter<'g>(guard: &'g Self) -> <&'g T as IntoIterator>::IntoIter where &'g T: IntoIterator {
+        (&*guard.data).into_iter()
+    }
+
+    /**
+    Turns the guard into an iterator over the locked collection, which holds the lock until it's dropped.
+
+    This lets you return "an iterator over the locked `Vec`" from a function.  Items are cloned,
+    because a reference could outlive the iterator, and with it the lock.
+    */
+    pub fn into_guarded_iter<E>(guard: Self) -> GuardedIter<'a, T, <&'a T as IntoIterator>::IntoIter>
+    where &'a T: IntoIterator<Item = &'a E>, E: Clone + 'a {
+        let lock = guard.lock;
+        core::mem::forget(guard);
+        //Safety: we hold the lock, and the iterator is dropped before we unlock
+        let data: &'a T = unsafe { &*lock.data.get() };
+        GuardedIter { lock, iter: ManuallyDrop::new(data.into_iter()) }
+    }
+}
+
+/**
+An iterator that holds an [AtomicLock], from [Guard::into_guarded_iter].
+
+Unlocks when dropped.
+*/
+#[must_use]
+pub struct GuardedIter<'a, T, I> {
+    lock: &'a AtomicLock<T>,
+    iter: ManuallyDrop<I>,
+}
+
+impl<'a, T, I> Drop for GuardedIter<'a, T, I> {
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.iter) };
+        self.lock.unlock();
+    }
+}
+
+impl<'a, T, I, E> Iterator for GuardedIter<'a, T, I> where I: Iterator<Item = &'a E>, E: Clone + 'a {
+    type Item = E;
+    fn next(&mut self) -> Option<E> {
+        self.iter.next().cloned()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T, I, E> DoubleEndedIterator for GuardedIter<'a, T, I> where I: DoubleEndedIterator<Item = &'a E>, E: Clone + 'a {
+    fn next_back(&mut self) -> Option<E> {
+        self.iter.next_back().cloned()
+    }
+}
+
+impl<'a, T, I, E> ExactSizeIterator for GuardedIter<'a, T, I> where I: ExactSizeIterator<Item = &'a E>, E: Clone + 'a {}
+
+impl<'a, T, I, E> core::iter::FusedIterator for GuardedIter<'a, T, I> where I: core::iter::FusedIterator<Item = &'a E>, E: Clone + 'a {}
+
+impl<'a, T, I> core::fmt::Debug for GuardedIter<'a, T, I> where I: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GuardedIter")
+            .field("iter", &*self.iter)
+            .finish()
+    }
+}
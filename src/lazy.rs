@@ -0,0 +1,138 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A cell initialized once, then read without locking. */
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+const UNINIT: u8 = 0;
+const INITIALIZING: u8 = 1;
+const INIT: u8 = 2;
+
+/**
+A cell that is initialized at most once, by whoever gets there first, and read lock-free after.
+
+The value is stored in a `MaybeUninit<T>` next to a one-byte flag, so there is no size or branch
+overhead from an `Option<T>`, which matters for large payloads and types without a niche.
+
+As with [AtomicLock](crate::AtomicLock), nothing waits: if another thread is initializing,
+[AtomicLazyCell::try_init] returns None, and what you do about that is up to you.
+*/
+pub struct AtomicLazyCell<T> {
+    state: AtomicU8,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> AtomicLazyCell<T> {
+    /**
+    Creates a new, uninitialized cell.
+    */
+    pub const fn new() -> Self {
+        AtomicLazyCell {
+            state: AtomicU8::new(UNINIT),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /**
+    The value, if the cell has been initialized.  A single atomic load.
+    */
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Ordering::Acquire) == INIT {
+            Some(unsafe { (*self.data.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Initializes the cell with `f`, unless it's already initialized, and returns the value.
+
+    Returns None if another thread is initializing it right now; `f` is not called.
+    If `f` panics, the cell stays uninitialized.
+    */
+    pub fn try_init<F>(&self, f: F) -> Option<&T> where F: FnOnce() -> T {
+        if let Some(data) = self.get() {
+            return Some(data);
+        }
+        match self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => {}
+            //lost the race to a finished initializer
+            Err(INIT) => return self.get(),
+            Err(_) => return None,
+        }
+        //put the flag back if f panics
+        struct Reset<'a>(&'a AtomicU8);
+        impl Drop for Reset<'_> {
+            fn drop(&mut self) {
+                self.0.store(UNINIT, Ordering::Release);
+            }
+        }
+        let reset = Reset(&self.state);
+        let data = f();
+        core::mem::forget(reset);
+        let data: &T = unsafe { (*self.data.get()).write(data) };
+        self.state.store(INIT, Ordering::Release);
+        Some(data)
+    }
+
+    /**
+    The value, mutably, if the cell has been initialized.
+    */
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if *self.state.get_mut() == INIT {
+            Some(unsafe { self.data.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Consumes the cell, returning the value if it was initialized.
+    */
+    pub fn into_inner(self) -> Option<T> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        if *this.state.get_mut() == INIT {
+            Some(unsafe { this.data.get_mut().assume_init_read() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Drop for AtomicLazyCell<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == INIT {
+            unsafe { self.data.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+impl<T> Default for AtomicLazyCell<T> {
+    fn default() -> Self {
+        AtomicLazyCell::new()
+    }
+}
+
+impl<T> From<T> for AtomicLazyCell<T> {
+    fn from(data: T) -> Self {
+        AtomicLazyCell {
+            state: AtomicU8::new(INIT),
+            data: UnsafeCell::new(MaybeUninit::new(data)),
+        }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for AtomicLazyCell<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.get() {
+            Some(data) => f.debug_tuple("AtomicLazyCell").field(data).finish(),
+            None => f.write_str("AtomicLazyCell(<uninit>)"),
+        }
+    }
+}
+
+//readers on many threads share &T, and whichever thread initializes it may not be the one that drops it
+unsafe impl<T: Send> Send for AtomicLazyCell<T> {}
+unsafe impl<T: Send + Sync> Sync for AtomicLazyCell<T> {}
@@ -0,0 +1,98 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Spotting guards that were leaked, leaving their lock held forever. */
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+An [AtomicLock] that counts its acquisitions, so a monitor can spot a guard that was
+[core::mem::forget]-ten and left the lock held for good.
+
+There's no telling a leaked guard from one that's just held for a long time, so this is a heuristic.
+Take a [LeakProbe] now, and check [LeakCheckedLock::is_likely_leaked] with it later: if the lock was
+held throughout, by the same acquisition, it's likely leaked.  How much later is up to you, and should be
+longer than you ever expect to hold the lock.
+*/
+#[derive(Debug, Default)]
+pub struct LeakCheckedLock<T> {
+    lock: AtomicLock<T>,
+    generation: AtomicUsize,
+}
+
+impl<T> LeakCheckedLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        LeakCheckedLock {
+            lock: AtomicLock::new(data),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<Guard<'_, T>> {
+        let guard = self.lock.lock()?;
+        self.generation.fetch_add(1, Ordering::Release);
+        Some(guard)
+    }
+
+    /**
+    Whether the lock is currently held.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+
+    /**
+    The number of times the lock has been acquired, wrapping on overflow.
+    */
+    pub fn generation(&self) -> usize {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /**
+    Notes the lock's state now, for [LeakCheckedLock::is_likely_leaked] to compare against later.
+    */
+    pub fn leak_probe(&self) -> LeakProbe {
+        let generation = self.generation();
+        LeakProbe { generation, locked: self.is_locked() }
+    }
+
+    /**
+    Whether the lock has been held since `probe` was taken, without ever being released.
+
+    If that's longer than the lock should ever be held, the guard was likely leaked.
+    May briefly report true just as the lock changes hands, so check again before raising the alarm.
+    */
+    pub fn is_likely_leaked(&self, probe: LeakProbe) -> bool {
+        probe.locked && self.is_locked() && self.generation() == probe.generation
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T> From<T> for LeakCheckedLock<T> {
+    fn from(data: T) -> Self {
+        LeakCheckedLock::new(data)
+    }
+}
+
+/**
+A snapshot of a [LeakCheckedLock], from [LeakCheckedLock::leak_probe].
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LeakProbe {
+    generation: usize,
+    locked: bool,
+}
@@ -0,0 +1,178 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locks that are held for a lease, which others can reclaim once it expires. */
+
+use crate::{AtomicLock, Clock, Guard};
+
+/**
+A lock whose guards hold it for a lease of `lease` ticks of `clock`.
+
+Once a lease expires, the next [LeaseLock::lock] reclaims the lock, so a stuck holder can't wedge
+everyone else forever.  To make that safe, a [LeaseGuard] doesn't hand out the data directly: each
+[LeaseGuard::with] checks that the lease is still current, and fails if it expired or was reclaimed.
+The holder can [LeaseGuard::renew] the lease before it runs out.
+
+The lease is checked when an operation starts, so a holder that gets stuck inside
+[LeaseGuard::with] still can't be reclaimed from; keep those operations short.
+*/
+pub struct LeaseLock<T, C> {
+    lock: AtomicLock<Leased<T>>,
+    clock: C,
+    lease: u64,
+}
+
+#[derive(Debug)]
+struct Leased<T> {
+    //bumped on every acquisition, so a guard can tell it's been reclaimed
+    generation: usize,
+    held: bool,
+    start: u64,
+    value: T,
+}
+
+impl<T, C: Clock> LeaseLock<T, C> {
+    /**
+    Creates a new lock, whose guards hold it for `lease` ticks of `clock`.
+    */
+    pub const fn new(data: T, clock: C, lease: u64) -> Self {
+        LeaseLock {
+            lock: AtomicLock::new(Leased { generation: 0, held: false, start: 0, value: data }),
+            clock,
+            lease,
+        }
+    }
+
+    /**
+    Takes a lease on the lock, if it's free or the last lease expired.
+
+    Returns None if the lock is leased, or busy with a [LeaseGuard::with] that is running right now.
+    */
+    pub fn lock(&self) -> Option<LeaseGuard<'_, T, C>> {
+        let mut leased = self.lock.lock()?;
+        let now = self.clock.now();
+        if leased.held && now.wrapping_sub(leased.start) < self.lease {
+            return None;
+        }
+        leased.generation = leased.generation.wrapping_add(1);
+        leased.held = true;
+        leased.start = now;
+        Some(LeaseGuard { lock: self, generation: leased.generation })
+    }
+
+    /**
+    Whether the lock is leased, and the lease hasn't expired.
+
+    Returns None if the lock is busy with a [LeaseGuard::with] that is running right now.
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_leased(&self) -> Option<bool> {
+        let leased = self.lock.lock()?;
+        Some(leased.held && self.clock.now().wrapping_sub(leased.start) < self.lease)
+    }
+
+    /**
+    The length of a lease, in ticks.
+    */
+    pub fn lease(&self) -> u64 {
+        self.lease
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner().value
+    }
+
+    //others only hold the inner lock briefly: to check a lease, or for the current holder's with
+    fn lock_spin(&self) -> Guard<'_, Leased<T>> {
+        loop {
+            if let Some(guard) = self.lock.lock() {
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<T, C> core::fmt::Debug for LeaseLock<T, C> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LeaseLock")
+            .field("lock", &self.lock)
+            .field("lease", &self.lease)
+            .finish_non_exhaustive()
+    }
+}
+
+/**
+A lease on a [LeaseLock].
+
+Ends the lease when dropped, unless it already expired.
+*/
+#[must_use]
+pub struct LeaseGuard<'a, T, C: Clock> {
+    lock: &'a LeaseLock<T, C>,
+    generation: usize,
+}
+
+impl<'a, T, C: Clock> LeaseGuard<'a, T, C> {
+    fn current(&self, leased: &Leased<T>) -> bool {
+        leased.held && leased.generation == self.generation
+            && self.lock.clock.now().wrapping_sub(leased.start) < self.lock.lease
+    }
+
+    /**
+    Runs `f` on the data, if the lease is still current.
+
+    Returns None without running `f` if the lease expired, or the lock was reclaimed.
+
+    Don't touch the lock from `f`.  [LeaseLock::lock] and [LeaseLock::is_leased] return None there,
+    but [LeaseGuard::renew], [LeaseGuard::is_current] and dropping any [LeaseGuard] for the same
+    lock wait for `f` to return, so doing any of those from `f` deadlocks.
+    */
+    pub fn with<R, F>(&mut self, f: F) -> Option<R> where F: FnOnce(&mut T) -> R {
+        let mut leased = self.lock.lock_spin();
+        if !self.current(&leased) {
+            return None;
+        }
+        Some(f(&mut leased.value))
+    }
+
+    /**
+    Starts the lease over, if it's still current.
+
+    Returns false if the lease expired, or the lock was reclaimed.
+    */
+    pub fn renew(&mut self) -> bool {
+        let mut leased = self.lock.lock_spin();
+        if !self.current(&leased) {
+            return false;
+        }
+        leased.start = self.lock.clock.now();
+        true
+    }
+
+    /**
+    Whether the lease is still current.
+
+    It may expire right after you look.
+    */
+    pub fn is_current(&self) -> bool {
+        self.current(&self.lock.lock_spin())
+    }
+}
+
+impl<'a, T, C: Clock> Drop for LeaseGuard<'a, T, C> {
+    fn drop(&mut self) {
+        let mut leased = self.lock.lock_spin();
+        //an expired lease may have been reclaimed already, and then it's not ours to end
+        if leased.generation == self.generation {
+            leased.held = false;
+        }
+    }
+}
+
+impl<'a, T, C: Clock> core::fmt::Debug for LeaseGuard<'a, T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LeaseGuard").field("generation", &self.generation).finish_non_exhaustive()
+    }
+}
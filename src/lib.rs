@@ -1,5 +1,9 @@
 //SPDX-License-Identifier: MIT OR Apache-2.0
 #![no_std]
+//the wasm wait/notify intrinsics are unstable, but the atomics target feature needs nightly anyway
+#![cfg_attr(all(feature = "futex", target_arch = "wasm32", target_feature = "atomics"), feature(stdarch_wasm_atomic_wait))]
+//opt in with RUSTFLAGS="--cfg atomiclock_nightly", so --all-features keeps working on stable
+#![cfg_attr(atomiclock_nightly, feature(must_not_suspend))]
 
 
 /*! A Rust atomic lock type.
@@ -9,18 +13,297 @@
 This is a simple atomic lock.
 
 There is no way to sleep the current thread if the lock is not available, what you do about that is up to you.
+
+On targets without atomic compare-and-swap, [AtomicLock] falls back to a plain `Cell`.  The API is the same,
+but the lock is not `Sync`, so it can only be used from one thread.
 */
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::cell::UnsafeCell;
 use core::fmt::{Debug, Display};
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::mem::MaybeUninit;
+use state::State;
+
+mod adaptors;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod arc;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+mod async_barrier;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+mod async_once;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+mod async_rwlock;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+mod async_semaphore;
+mod async_yield;
+mod attempts;
+#[cfg(target_has_atomic = "ptr")]
+mod barrier;
+#[cfg(target_has_atomic = "8")]
+mod biased;
+#[cfg(target_has_atomic = "64")]
+mod bitpool;
+#[cfg(feature = "alloc")]
+mod boxed;
+mod brand;
+#[cfg(target_has_atomic = "8")]
+mod cancel;
+mod ceiling;
+mod clock;
+#[cfg(target_has_atomic = "ptr")]
+mod cohort;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod combining;
+#[cfg(all(feature = "lock_api", target_has_atomic = "ptr"))]
+pub mod compat;
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+mod condition;
+#[cfg(target_has_atomic = "ptr")]
+mod cow;
+mod diagnostics;
+#[cfg(target_has_atomic = "ptr")]
+mod double;
+#[cfg(feature = "alloc")]
+mod downcast;
+#[cfg(all(feature = "elision", target_arch = "x86_64"))]
+mod elision;
+mod erased;
+#[cfg(all(feature = "futex", any(target_os = "linux", target_os = "windows", target_os = "macos", all(target_arch = "wasm32", target_feature = "atomics"))))]
+mod futex;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod group;
+#[cfg(feature = "std")]
+mod hash_map;
+#[cfg(target_has_atomic = "ptr")]
+mod hierarchy;
+#[cfg(feature = "holder-backtrace")]
+mod holder;
+mod hooked;
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+mod hybrid;
+#[cfg(target_has_atomic = "64")]
+mod inline;
+#[cfg(target_has_atomic = "ptr")]
+mod invariant;
+mod iter;
+#[cfg(target_has_atomic = "8")]
+mod lazy;
+mod lease;
+#[cfg(target_has_atomic = "ptr")]
+mod leak;
+mod local;
+mod lock_ref;
+#[cfg(feature = "alloc")]
+mod many;
+mod mapped;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+mod notify;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod owned;
+#[cfg(any(feature = "alloc", target_has_atomic = "ptr"))]
+mod padded;
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+mod parker;
+mod predicate;
+#[cfg(target_has_atomic = "ptr")]
+mod priority;
+#[cfg(feature = "profiling")]
+mod profiling;
+#[cfg(kani)]
+mod proofs;
+#[cfg(target_has_atomic = "ptr")]
+mod queue;
+#[cfg(all(feature = "lock_api", target_has_atomic = "ptr"))]
+mod raw;
+mod range;
+#[cfg(feature = "alloc")]
+mod rc;
+mod read_only;
+#[cfg(all(feature = "flight-recorder", target_has_atomic = "ptr"))]
+mod recorder;
+#[cfg(target_has_atomic = "ptr")]
+mod rwlock;
+#[cfg(feature = "zeroize")]
+mod secret;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(target_has_atomic = "64")]
+mod shared;
+#[cfg(feature = "stable_deref_trait")]
+mod stable_deref;
+mod state;
+#[cfg(feature = "thread-stats")]
+mod thread_stats;
+mod transaction;
+mod try_lock;
+mod uninit;
+#[cfg(feature = "alloc")]
+mod vec;
+#[cfg(target_has_atomic = "ptr")]
+mod versioned;
+#[cfg(target_has_atomic = "ptr")]
+mod wait_group;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+mod waker_queue;
+#[cfg(target_has_atomic = "ptr")]
+mod watch;
+#[cfg(all(feature = "watchdog", target_has_atomic = "ptr"))]
+mod watchdog;
+#[cfg(all(feature = "yield-points", target_has_atomic = "ptr"))]
+mod yield_point;
+
+pub use adaptors::{Spin, Timeout};
+#[cfg(feature = "std")]
+pub use adaptors::Yielding;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use arc::ArcLock;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+pub use async_barrier::{AsyncBarrier, BarrierWait};
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+pub use async_once::AsyncOnceCell;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+pub use async_rwlock::{AsyncReadGuard, AsyncRwLock, AsyncWriteGuard, Read, Write};
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+pub use async_semaphore::{Acquire, AsyncSemaphore, SemaphorePermit};
+#[cfg(feature = "async")]
+pub use async_yield::LockFuture;
+pub use async_yield::{yield_now, YieldNow};
+pub use attempts::LockAttempts;
+#[cfg(target_has_atomic = "ptr")]
+pub use barrier::{Arrival, TryBarrier};
+#[cfg(target_has_atomic = "8")]
+pub use biased::{BiasHandle, BiasedGuard, BiasedLock};
+#[cfg(target_has_atomic = "64")]
+pub use bitpool::{BitLockGuard, BitLockPool};
+pub use brand::{BrandCell, BrandLock, BrandToken};
+#[cfg(target_has_atomic = "8")]
+pub use cancel::Cancelled;
+pub use ceiling::{CeilingGuard, CeilingLock, PriorityHooks};
+pub use clock::Clock;
+#[cfg(feature = "std")]
+pub use clock::StdClock;
+#[cfg(target_has_atomic = "ptr")]
+pub use cohort::{CohortGuard, CohortLock};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use combining::{CombiningGuard, CombiningLock};
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+pub use condition::AtomicCondition;
+#[cfg(target_has_atomic = "ptr")]
+pub use cow::{CowGuard, CowLock, CowRead};
+pub use diagnostics::{DiagnosticsReport, LockId};
+#[cfg(target_has_atomic = "ptr")]
+pub use double::{BackGuard, DoubleBuffered, FrontGuard};
+#[cfg(all(feature = "elision", target_arch = "x86_64"))]
+pub use elision::{ElisionGuard, ElisionLock};
+pub use erased::ErasedGuard;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use group::{GroupGuard, LockGroup};
+#[cfg(feature = "std")]
+pub use hash_map::LockedHashMap;
+#[cfg(target_has_atomic = "ptr")]
+pub use hierarchy::{IntentGuard, IntentLock};
+#[cfg(feature = "holder-backtrace")]
+pub use holder::{held_locks, LockHolder};
+pub use hooked::{HookedGuard, HookedLock};
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+pub use hybrid::{HybridGuard, HybridLock, HybridPolicy};
+#[cfg(target_has_atomic = "64")]
+pub use inline::{InlineAtomicLock, InlineGuard, InlineValue};
+#[cfg(target_has_atomic = "ptr")]
+pub use invariant::{InvariantGuard, InvariantLock};
+pub use iter::GuardedIter;
+#[cfg(target_has_atomic = "8")]
+pub use lazy::AtomicLazyCell;
+pub use lease::{LeaseGuard, LeaseLock};
+#[cfg(target_has_atomic = "ptr")]
+pub use leak::{LeakCheckedLock, LeakProbe};
+pub use local::LocalAtomicLock;
+pub use lock_ref::{AtomicLockRef, RefGuard};
+pub use mapped::MappedGuard;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+pub use notify::{Notified, Notify};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use owned::{OwnedGuard, OwnedMappedGuard};
+#[cfg(target_has_atomic = "ptr")]
+pub use priority::{Intent, PriorityLock};
+#[cfg(feature = "profiling")]
+pub use profiling::ProfilerHooks;
+#[cfg(all(feature = "profiling", target_has_atomic = "ptr"))]
+pub use profiling::set_profiler;
+#[cfg(target_has_atomic = "ptr")]
+pub use queue::LockedQueue;
+pub use range::{RangeGuard, RangeLock};
+#[cfg(feature = "alloc")]
+pub use rc::RcGuard;
+pub use read_only::ReadOnly;
+#[cfg(all(feature = "flight-recorder", target_has_atomic = "ptr"))]
+pub use recorder::{ContentionEvent, ContentionLog, FoldedContention, RecordedLock};
+#[cfg(target_has_atomic = "ptr")]
+pub use rwlock::{AtomicRwLock, MappedReadGuard, ReadGuard, RwLockStats, WriteGuard};
+#[cfg(all(feature = "lock_api", target_has_atomic = "ptr"))]
+pub use raw::RawAtomicLock;
+#[cfg(all(feature = "lock_api", target_has_atomic = "ptr"))]
+pub use rwlock::RawAtomicRwLock;
+#[cfg(feature = "zeroize")]
+pub use secret::SecretAtomicLock;
+#[cfg(target_has_atomic = "64")]
+pub use shared::{SharedGuard, SharedMemoryLock};
+pub use state::CellState;
+pub use state::StateWord;
+#[cfg(feature = "thread-stats")]
+pub use thread_stats::{current_thread_lock_stats, reset_thread_lock_stats, thread_lock_stats, ThreadLockStats};
+pub use transaction::TransactionalGuard;
+pub use try_lock::{TryLock, TryLockRead, TryLockWrite};
+pub use uninit::UninitGuard;
+#[cfg(feature = "alloc")]
+pub use vec::AtomicLockVec;
+#[cfg(target_has_atomic = "ptr")]
+pub use versioned::{VersionedGuard, VersionedLock};
+#[cfg(target_has_atomic = "ptr")]
+pub use wait_group::TryWaitGroup;
+#[cfg(target_has_atomic = "ptr")]
+pub use watch::{LockWatcher, WatchedGuard, WatchedLock, WatchToken};
+#[cfg(all(feature = "watchdog", target_has_atomic = "ptr"))]
+pub use watchdog::{set_long_hold_handler, LongHold, WatchdogGuard, WatchdogLock};
+#[cfg(all(feature = "watchdog", feature = "std", target_has_atomic = "ptr"))]
+pub use watchdog::warn_long_hold;
+#[cfg(all(feature = "yield-points", target_has_atomic = "ptr"))]
+pub use yield_point::{set_yield_hook, YieldHook, YieldPoint};
+#[cfg(all(feature = "yield-points", target_has_atomic = "64"))]
+pub use yield_point::{seeded_yield, set_yield_seed};
+
+/*
+The lock state is a single byte.  LOCKED is the lock; UNINIT marks a lock from AtomicLock::uninit
+whose data nobody has started to initialize.  The other bits are reserved so that poisoning,
+contention and fairness flags can be added later without changing the layout or adding atomics.
+Code that changes the state must preserve bits it doesn't know about.
+ */
+pub(crate) const LOCKED: u8 = 1 << 0;
+#[allow(dead_code)]
+pub(crate) const POISONED: u8 = 1 << 1;
+#[allow(dead_code)]
+pub(crate) const CONTENDED: u8 = 1 << 2;
+#[allow(dead_code)]
+pub(crate) const FAIR: u8 = 1 << 3;
+pub(crate) const UNINIT: u8 = 1 << 4;
 
 /**
 An atomic lock type.
 
+The layout is `#[repr(C)]`: the state word, then `T`.  That makes it usable in memory shared
+between processes with the same `T`, though if a holder dies the lock stays locked;
+see [SharedMemoryLock] for recovery.
+
+The state word is one byte by default.  To use another, like a C struct's `uint32_t` lock word,
+pick an `S` from [StateWord] and create the lock with [AtomicLock::with_state].
 */
-pub struct AtomicLock<T> {
-    lock: AtomicBool,
+#[repr(C)]
+pub struct AtomicLock<T, S = State> {
+    lock: S,
     data: UnsafeCell<T>,
 }
 
@@ -30,10 +313,59 @@ impl<T> AtomicLock<T> {
 */
     pub const fn new(data: T) -> Self {
         AtomicLock {
-            lock: AtomicBool::new(false),
+            lock: State::new(0),
             data: UnsafeCell::new(data),
         }
     }
+}
+
+impl<T> AtomicLock<T> {
+    /**
+    Initializes a lock in `slot`, with `f` initializing the data where it will live, and returns it.
+
+    The data never exists by value, so even a payload too big for the stack works,
+    as long as `f` initializes it piece by piece.  With the `alloc` feature,
+    `AtomicLock::new_in_place` does this on the heap.
+
+    # Safety
+    `f` must fully initialize the data.
+    */
+    pub unsafe fn init_in_place<F>(slot: &mut MaybeUninit<Self>, f: F) -> &mut Self where F: FnOnce(&mut MaybeUninit<T>) {
+        let ptr = slot.as_mut_ptr();
+        core::ptr::addr_of_mut!((*ptr).lock).write(State::new(0));
+        //UnsafeCell<T> and MaybeUninit<T> both have the same layout as T
+        f(&mut *(core::ptr::addr_of_mut!((*ptr).data) as *mut MaybeUninit<T>));
+        &mut *ptr
+    }
+}
+
+impl<'a, T: ?Sized> AtomicLock<&'a mut T> {
+    /**
+    Creates a lock over data you already own exclusively, without moving it.
+
+    Unlike `Cell::from_mut`, this can't return a `&AtomicLock<T>`, since the lock needs a state word
+    next to the data.  Instead it's a lock over the borrow, which you can share, e.g. with scoped threads,
+    and which gives the data back when it goes out of scope.
+    */
+    pub fn from_mut(data: &'a mut T) -> Self {
+        AtomicLock::new(data)
+    }
+}
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    Creates a new lock with the given state word, which should be unlocked.
+
+    For example, `AtomicLock::with_state(AtomicU32::new(0), data)`.  A word with the locked bit set
+    makes a lock that stays locked until someone calls [AtomicLock::unlock].
+    */
+    pub const fn with_state(state: S, data: T) -> Self {
+        AtomicLock {
+            lock: state,
+            data: UnsafeCell::new(data),
+        }
+    }
+
     /**
     Locks the lock and accesses the data if available.
     If the lock is unavailable, will return None.
@@ -46,25 +378,74 @@ impl<T> AtomicLock<T> {
 
     It's up to you!
     */
-    pub fn lock(&self) -> Option<Guard<T>> {
-        match self.lock.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed) {
-            Ok(_) => Some(
+    #[inline]
+    pub fn lock(&self) -> Option<Guard<'_, T, S>> {
+        let guard = self.acquire();
+        #[cfg(feature = "profiling")]
+        if guard.is_some() {
+            profiling::hold_begin(self as *const Self as *const ());
+        }
+        #[cfg(feature = "thread-stats")]
+        if guard.is_none() {
+            thread_stats::failed();
+        }
+        guard
+    }
+
+    /*
+    lock, without telling the profiler.  Waiting code uses this, so it can close its wait zone
+    before the hold zone opens.
+     */
+    #[inline]
+    pub(crate) fn acquire(&self) -> Option<Guard<'_, T, S>> {
+        #[cfg(all(feature = "yield-points", target_has_atomic = "ptr"))]
+        yield_point::yield_point(yield_point::YieldPoint::Acquire, self as *const Self as *const ());
+        if self.lock.try_acquire() {
+            Some(
                 Guard {
                     lock: self,
                     data: unsafe { &mut *self.data.get() },
                 }
-
-            ),
-            Err(_) => None,
+            )
+        } else {
+            contended()
         }
     }
 
     /**
     Unlocks the current lock.
 */
+    #[inline]
     pub fn unlock(&self) {
-        let old = self.lock.swap(false, Ordering::Release);
-        assert_eq!(old, true);
+        unlock(&self.lock);
+    }
+
+    /**
+    Whether the lock is currently held.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_held()
+    }
+
+    /** Unlocks the lock out from under its holder, for recovering from a holder that will never unlock it.
+
+    For example, a thread that died holding the lock, or a guard that was leaked.
+    Does nothing if the lock isn't held, and never panics, unlike [AtomicLock::unlock] in debug builds.
+
+    The data is left however the holder left it, which may be halfway through an update.
+
+    # Safety
+    The holder's guard, if there is one, must never be used or dropped again; otherwise it would
+    access the data alongside the next holder, or unlock their lock.
+    */
+    pub unsafe fn force_unlock(&self) {
+        #[cfg(feature = "profiling")]
+        if self.lock.is_held() {
+            profiling::hold_end(self as *const Self as *const ());
+        }
+        self.lock.force_release();
     }
 
     /** Unsafely access the underlying data.
@@ -72,10 +453,25 @@ impl<T> AtomicLock<T> {
     # Safety
     You must ensure that no other readers or writers are accessing the lock.
     */
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn data(&self) -> &mut T {
         &mut *self.data.get()
     }
 
+    /** Reads the data without taking the lock, for watchdogs and crash handlers that sample state.
+
+    Unlike [AtomicLock::data], this only hands out a shared reference, and is meant for
+    reading while someone else may hold the lock.
+
+    # Safety
+    If anyone writes the data while the reference is alive, that is a data race: undefined behavior,
+    not just a torn read.  You must ensure that can't happen, e.g. because the writers are stopped,
+    or because the part you read is only ever written through atomics or `UnsafeCell`.
+    */
+    pub unsafe fn peek_unsynchronized(&self) -> &T {
+        &*self.data.get()
+    }
+
     /**
     Conumes the lock, returning the inner data.
     */
@@ -85,22 +481,55 @@ impl<T> AtomicLock<T> {
 
 }
 
+//clears LOCKED; shared with guards that only keep the state, like ErasedGuard
+#[inline]
+pub(crate) fn unlock<S: StateWord>(lock: &S) {
+    //the state is first in our repr(C) layout, so this is the lock's address too
+    #[cfg(feature = "profiling")]
+    profiling::hold_end(lock as *const S as *const ());
+    #[cfg(all(feature = "yield-points", target_has_atomic = "ptr"))]
+    yield_point::yield_point(yield_point::YieldPoint::Release, lock as *const S as *const ());
+    lock.release();
+}
 
-impl<T: Debug> Debug for AtomicLock<T> {
+//out of line, so the compiler lays out the successful path first
+#[cold]
+fn contended<'a, T, S: StateWord>() -> Option<Guard<'a, T, S>> {
+    None
+}
+
+
+impl<T: Debug, S: StateWord> Debug for AtomicLock<T, S> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let guard = self.lock();
-        match guard {
+        //acquire, so looking at the lock doesn't count as using it in stats, profiles or holder records
+        match self.acquire() {
             None => {
-                f.debug_struct("AtomicLock")
-                    .field("locked", &true)
-                    .field("data", &"<Locked>")
+                let mut debug = f.debug_struct("AtomicLock");
+                debug.field("locked", &true);
+                #[cfg(feature = "holder-backtrace")]
+                match holder::try_holder(self as *const Self as *const ()) {
+                    Some(Some(holder)) => {
+                        debug.field("holder", &holder.thread_id())
+                            .field("held_for", &holder.held_for());
+                    }
+                    Some(None) => {}
+                    None => {
+                        debug.field("holder", &format_args!("<holder table busy>"));
+                    }
+                }
+                debug.field("data", &format_args!("<locked>"))
                     .finish()
             }
-            Some(data) => {
-                f.debug_struct("AtomicLock")
+            Some(guard) => {
+                //show the payload itself, the guard wrapper is just noise here
+                let result = f.debug_struct("AtomicLock")
                     .field("locked", &false)
-                    .field("data", &data)
-                    .finish()
+                    .field("data", &*guard)
+                    .finish();
+                //the hold was never reported, so neither is the release
+                core::mem::forget(guard);
+                self.lock.release();
+                result
             }
         }
     }
@@ -110,16 +539,20 @@ impl<T: Debug> Debug for AtomicLock<T> {
 A guard for [AtomicLock].
 
 Unlocks when dropped.
-*/
 
-#[derive(Debug)]
+Holding a guard across an `.await` blocks everyone else for as long as the task is suspended.
+On nightly, build with `--cfg atomiclock_nightly` and enable the `must_not_suspend` lint to catch that.
+*/
 #[must_use]
-pub struct Guard<'a, T> {
-    lock: &'a AtomicLock<T>,
+#[cfg_attr(atomiclock_nightly, must_not_suspend = "other tasks can't take the lock while this one is suspended")]
+#[clippy::has_significant_drop]
+pub struct Guard<'a, T, S: StateWord = State> {
+    lock: &'a AtomicLock<T, S>,
     data: &'a mut T,
 }
 
-impl<'a, T> Drop for Guard<'a, T> {
+impl<'a, T, S: StateWord> Drop for Guard<'a, T, S> {
+    #[inline]
     fn drop(&mut self) {
         self.lock.unlock();
     }
@@ -154,8 +587,10 @@ impl <T> From<T> for AtomicLock<T> {
 
 //send and sync are ok
 
-unsafe impl<T> Send for AtomicLock<T> {}
-unsafe impl<T> Sync for AtomicLock<T> {}
+unsafe impl<T: Send, S: Send> Send for AtomicLock<T, S> {}
+//sharing the lock hands the data to whichever thread locks it, so T needs only Send.
+//without atomics, the state is a plain Cell, which is only correct on one thread
+unsafe impl<T: Send, S: Sync> Sync for AtomicLock<T, S> {}
 
 /*now let's examine the guard boilerplate.
 
@@ -166,9 +601,19 @@ Guard cannot be cloned because doing so would involve locking a second time.  Si
 
 //default makes no sense and should not be done
 
+//debug shows the data; the lock is known to be held, so printing it would only say "<locked>"
+
+impl <'a, T, S: StateWord> Debug for Guard<'a, T, S> where T: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Guard")
+            .field("data", &*self.data)
+            .finish()
+    }
+}
+
 //display,
 
-impl <'a, T> Display for Guard<'a, T> where T: Display {
+impl <'a, T, S: StateWord> Display for Guard<'a, T, S> where T: Display {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.data.fmt(f)
     }
@@ -178,13 +623,13 @@ impl <'a, T> Display for Guard<'a, T> where T: Display {
 
 //asref/mut should be fine since we have the lock...
 
-impl <'a, T> AsRef<T> for Guard<'a, T> {
+impl <'a, T, S: StateWord> AsRef<T> for Guard<'a, T, S> {
     fn as_ref(&self) -> &T {
         self.data
     }
 }
 
-impl <'a, T> AsMut<T> for Guard<'a, T> {
+impl <'a, T, S: StateWord> AsMut<T> for Guard<'a, T, S> {
     fn as_mut(&mut self) -> &mut T {
         self.data
     }
@@ -192,14 +637,16 @@ impl <'a, T> AsMut<T> for Guard<'a, T> {
 
 //deref/derefmut should be fine since we have the lock...
 
-impl <'a, T> core::ops::Deref for Guard<'a, T> {
+impl <'a, T, S: StateWord> core::ops::Deref for Guard<'a, T, S> {
     type Target = T;
+    #[inline]
     fn deref(&self) -> &T {
         self.data
     }
 }
 
-impl <'a, T> core::ops::DerefMut for Guard<'a, T> {
+impl <'a, T, S: StateWord> core::ops::DerefMut for Guard<'a, T, S> {
+    #[inline]
     fn deref_mut(&mut self) -> &mut T {
         self.data
     }
@@ -213,4 +660,4 @@ as locked.
 
 We don't have those issues, so.
  */
-unsafe impl<'a, T> Send for Guard<'a, T> {}
\ No newline at end of file
+unsafe impl<'a, T: Send, S: StateWord + Sync> Send for Guard<'a, T, S> {}
\ No newline at end of file
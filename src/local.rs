@@ -0,0 +1,68 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A single-threaded twin of [AtomicLock]. */
+
+use crate::{AtomicLock, CellState, Guard};
+
+/**
+An [AtomicLock] for one thread, whose state is a `Cell` rather than an atomic.
+
+It has the same API: [LocalAtomicLock::lock] returns `Option<Guard>`, and it implements
+[TryLock](crate::TryLock), so generic code can run single-threaded without paying for atomics.
+It is not `Sync`, and its guards are not `Send`.
+
+Reentrancy is still detected: locking it again while a guard is alive returns None.
+*/
+#[derive(Debug)]
+pub struct LocalAtomicLock<T> {
+    lock: AtomicLock<T, CellState>,
+}
+
+impl<T> LocalAtomicLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        LocalAtomicLock { lock: AtomicLock::with_state(CellState::new(0), data) }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<Guard<'_, T, CellState>> {
+        self.lock.lock()
+    }
+
+    /**
+    Unlocks the current lock.
+    */
+    pub fn unlock(&self) {
+        self.lock.unlock()
+    }
+
+    /**
+    Whether the lock is currently held.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T: Default> Default for LocalAtomicLock<T> {
+    fn default() -> Self {
+        LocalAtomicLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for LocalAtomicLock<T> {
+    fn from(data: T) -> Self {
+        LocalAtomicLock::new(data)
+    }
+}
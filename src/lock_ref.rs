@@ -0,0 +1,141 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locks over a state word and data that live somewhere else. */
+
+use core::marker::PhantomData;
+use crate::state::State;
+use crate::StateWord;
+
+/**
+A lock made from an existing state word and the data it guards, which may not be next to each other.
+
+Existing structs, including C structs, often have their own lock byte or word.  Borrow it, and the data,
+as an `AtomicLockRef` to lock it the same way as an [AtomicLock](crate::AtomicLock).  Everyone touching
+the data has to go through the same state word.
+*/
+pub struct AtomicLockRef<'a, T, S: StateWord = State> {
+    lock: &'a S,
+    data: *mut T,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T, S: StateWord> AtomicLockRef<'a, T, S> {
+    /**
+    Borrows `data`, guarded by `state`.
+
+    # Safety
+    For `'a`, `state` must only be written through this crate's locks.  Otherwise other code could clear
+    the locked bit while a guard is out, and let a second guard to `data` in alongside it.
+    */
+    pub unsafe fn new(state: &'a S, data: &'a mut T) -> Self {
+        AtomicLockRef { lock: state, data, _marker: PhantomData }
+    }
+
+    /**
+    Creates a lock from a state word and a pointer to the data, e.g. from across FFI.
+
+    # Safety
+    `data` must be valid for `'a`, and only accessed while holding `state`, by this or any other lock.
+    As with [AtomicLockRef::new], `state` must only be written through this crate's locks.
+    */
+    pub unsafe fn from_raw(state: &'a S, data: *mut T) -> Self {
+        AtomicLockRef { lock: state, data, _marker: PhantomData }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    #[inline]
+    pub fn lock(&self) -> Option<RefGuard<'_, T, S>> {
+        if self.lock.try_acquire() {
+            //profiled by the state word's address, as crate::unlock reports it
+            #[cfg(feature = "profiling")]
+            crate::profiling::hold_begin(self.lock as *const S as *const ());
+            Some(RefGuard { lock: self.lock, data: unsafe { &mut *self.data } })
+        } else {
+            None
+        }
+    }
+
+    /**
+    Whether the lock is currently held.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_held()
+    }
+}
+
+impl<'a, T, S: StateWord> core::fmt::Debug for AtomicLockRef<'a, T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AtomicLockRef")
+            .field("locked", &self.is_locked())
+            .finish_non_exhaustive()
+    }
+}
+
+//like AtomicLock: the state word arbitrates access to the data
+unsafe impl<'a, T: Send, S: StateWord + Sync> Send for AtomicLockRef<'a, T, S> {}
+unsafe impl<'a, T: Send, S: StateWord + Sync> Sync for AtomicLockRef<'a, T, S> {}
+
+/**
+A guard for [AtomicLockRef].
+
+Unlocks when dropped.
+*/
+#[must_use]
+#[cfg_attr(atomiclock_nightly, must_not_suspend = "other tasks can't take the lock while this one is suspended")]
+#[clippy::has_significant_drop]
+pub struct RefGuard<'a, T, S: StateWord = State> {
+    lock: &'a S,
+    data: &'a mut T,
+}
+
+impl<'a, T, S: StateWord> Drop for RefGuard<'a, T, S> {
+    #[inline]
+    fn drop(&mut self) {
+        crate::unlock(self.lock);
+    }
+}
+
+impl<'a, T, S: StateWord> core::ops::Deref for RefGuard<'a, T, S> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        self.data
+    }
+}
+
+impl<'a, T, S: StateWord> core::ops::DerefMut for RefGuard<'a, T, S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        self.data
+    }
+}
+
+impl<'a, T, S: StateWord> AsRef<T> for RefGuard<'a, T, S> {
+    fn as_ref(&self) -> &T {
+        self.data
+    }
+}
+
+impl<'a, T, S: StateWord> AsMut<T> for RefGuard<'a, T, S> {
+    fn as_mut(&mut self) -> &mut T {
+        self.data
+    }
+}
+
+impl<'a, T, S: StateWord> core::fmt::Debug for RefGuard<'a, T, S> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RefGuard")
+            .field("data", &*self.data)
+            .finish()
+    }
+}
+
+impl<'a, T, S: StateWord> core::fmt::Display for RefGuard<'a, T, S> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.data.fmt(f)
+    }
+}
@@ -0,0 +1,25 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locking a dynamic collection of locks, all or nothing. */
+
+use alloc::vec::Vec;
+use crate::{AtomicLock, Guard};
+
+impl<T> AtomicLock<T> {
+    /**
+    Locks every lock in `locks`, or none of them.
+
+    Returns the guards in the same order as the locks.  If any lock is unavailable,
+    the ones already taken are unlocked again and this returns None.
+
+    A lock that appears twice is unavailable the second time, so this returns None.
+    */
+    pub fn try_lock_many<'a, I>(locks: I) -> Option<Vec<Guard<'a, T>>> where I: IntoIterator<Item = &'a AtomicLock<T>>, T: 'a {
+        let locks = locks.into_iter();
+        let mut guards = Vec::with_capacity(locks.size_hint().0);
+        for lock in locks {
+            //on failure, dropping `guards` rolls back
+            guards.push(lock.lock()?);
+        }
+        Some(guards)
+    }
+}
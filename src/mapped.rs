@@ -0,0 +1,138 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Guards projected onto a part of the locked data. */
+
+use core::fmt::{Debug, Display};
+use core::marker::PhantomData;
+use crate::state::State;
+use crate::{AtomicLock, Guard, StateWord};
+
+/**
+A guard for some part of the data inside an [AtomicLock].
+
+Created by [Guard::map] or the [project!](crate::project) macro.  Unlocks the original lock when dropped.
+*/
+#[must_use]
+#[cfg_attr(atomiclock_nightly, must_not_suspend = "other tasks can't take the lock while this one is suspended")]
+#[clippy::has_significant_drop]
+pub struct MappedGuard<'a, T, U, S: StateWord = State> {
+    lock: &'a AtomicLock<T, S>,
+    data: *mut U,
+    _marker: PhantomData<&'a mut U>,
+}
+
+impl<'a, T, S: StateWord> Guard<'a, T, S> {
+    /**
+    Makes a new [MappedGuard] for a component of the locked data.
+
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    Use it as `Guard::map(guard, |data| &mut data.field)`.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn map<U, F>(guard: Self, f: F) -> MappedGuard<'a, T, U, S> where F: FnOnce(&mut T) -> &mut U {
+        let lock = guard.lock;
+        let data: *mut U = f(unsafe { &mut *lock.data.get() });
+        core::mem::forget(guard);
+        MappedGuard {
+            lock,
+            data,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, U, S: StateWord> MappedGuard<'a, T, U, S> {
+    /**
+    Projects the guard further, onto a component of the already-mapped data.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn map<V, F>(guard: Self, f: F) -> MappedGuard<'a, T, V, S> where F: FnOnce(&mut U) -> &mut V {
+        let lock = guard.lock;
+        let data: *mut V = f(unsafe { &mut *guard.data });
+        core::mem::forget(guard);
+        MappedGuard {
+            lock,
+            data,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, U, S: StateWord> MappedGuard<'a, T, U, S> {
+    //gives up the guard without unlocking, for guards that take over the lock
+    pub(crate) fn into_lock(guard: Self) -> &'a AtomicLock<T, S> {
+        let lock = guard.lock;
+        core::mem::forget(guard);
+        lock
+    }
+}
+
+impl<'a, T, U, S: StateWord> Drop for MappedGuard<'a, T, U, S> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+/**
+Projects a [Guard] onto a nested field path.
+
+`project!(guard.field.subfield)` is shorthand for
+`Guard::map(guard, |data| &mut data.field.subfield)`.
+
+Works on a [MappedGuard] too, by writing `project!(mapped => field.subfield)`.
+*/
+#[macro_export]
+macro_rules! project {
+    ($guard:ident $(. $field:tt)+) => {
+        $crate::Guard::map($guard, |data| &mut data $(. $field)+)
+    };
+    ($guard:expr => $($field:tt).+) => {
+        $crate::MappedGuard::map($guard, |data| &mut data $(. $field)+)
+    };
+}
+
+//boilerplate, same reasoning as Guard
+
+impl<'a, T, U, S: StateWord> Debug for MappedGuard<'a, T, U, S> where U: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MappedGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T, U, S: StateWord> Display for MappedGuard<'a, T, U, S> where U: Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, T, U, S: StateWord> AsRef<U> for MappedGuard<'a, T, U, S> {
+    fn as_ref(&self) -> &U {
+        self
+    }
+}
+
+impl<'a, T, U, S: StateWord> AsMut<U> for MappedGuard<'a, T, U, S> {
+    fn as_mut(&mut self) -> &mut U {
+        self
+    }
+}
+
+impl<'a, T, U, S: StateWord> core::ops::Deref for MappedGuard<'a, T, U, S> {
+    type Target = U;
+    fn deref(&self) -> &U {
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, T, U, S: StateWord> core::ops::DerefMut for MappedGuard<'a, T, U, S> {
+    fn deref_mut(&mut self) -> &mut U {
+        unsafe { &mut *self.data }
+    }
+}
+
+//we only hold a *mut U for projection purposes, it behaves like &mut U
+unsafe impl<'a, T, U, S: StateWord + Sync> Send for MappedGuard<'a, T, U, S> where U: Send {}
+unsafe impl<'a, T, U, S: StateWord> Sync for MappedGuard<'a, T, U, S> where U: Sync {}
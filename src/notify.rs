@@ -0,0 +1,134 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Async notifications between tasks. */
+
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::task::{Context, Poll};
+use crate::waker_queue::{Registration, WakerQueue};
+
+/**
+Wakes tasks waiting on [Notify::notified], so async code can signal a change to state
+it guards with an [AtomicLock](crate::AtomicLock) instead of polling for it.
+
+[Notify::notify_one] wakes one waiting task, or if none are waiting, leaves a permit so the
+next `notified().await` completes right away.  [Notify::notify_waiters] wakes every
+task waiting now, and leaves nothing for later.
+*/
+#[derive(Debug)]
+pub struct Notify {
+    //a notify_one nobody was waiting for
+    permit: AtomicBool,
+    //bumped by notify_waiters; a Notified created before the bump completes
+    generation: AtomicUsize,
+    waiters: WakerQueue,
+}
+
+impl Notify {
+    /**
+    Creates a new `Notify` without a permit.
+    */
+    pub const fn new() -> Self {
+        Notify { permit: AtomicBool::new(false), generation: AtomicUsize::new(0), waiters: WakerQueue::new() }
+    }
+
+    /**
+    Wakes the task that has waited longest, or leaves a permit for the next one to wait.
+
+    Permits don't add up: notifying twice with nobody waiting leaves one.
+    */
+    pub fn notify_one(&self) {
+        //release pairs with the acquire in take_permit, so the waiter sees what we did before notifying
+        self.waiters.wake_one_or(|| self.permit.store(true, Ordering::Release));
+    }
+
+    /**
+    Wakes every task waiting right now, including [Notified] futures that have been
+    created but not yet polled.
+    */
+    pub fn notify_waiters(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.waiters.wake_all();
+    }
+
+    /**
+    Waits for a notification.
+
+    Notifications from [Notify::notify_waiters] count from when this is called, so you can
+    call it, check your condition, then await it, without missing one in between.
+    */
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            generation: self.generation.load(Ordering::SeqCst),
+            registration: Registration::new(&self.waiters),
+        }
+    }
+
+    fn take_permit(&self) -> bool {
+        self.permit.load(Ordering::Relaxed) && self.permit.swap(false, Ordering::Acquire)
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Notify::new()
+    }
+}
+
+/**
+Future returned by [Notify::notified].
+
+# Cancellation
+Dropping it gives up its place in the queue.  If [Notify::notify_one] had already picked it,
+the notification passes to the next waiting task, or is kept as the permit if there is none,
+so cancelling never loses a notification.
+*/
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    generation: usize,
+    registration: Registration<'a>,
+}
+
+impl<'a> Notified<'a> {
+    //completed some other way than our wakeup; if notify_one woke us too, pass it on, as in drop
+    fn finish(&mut self) -> Poll<()> {
+        if self.registration.take_wakeup() && self.notify.generation.load(Ordering::SeqCst) == self.generation {
+            self.notify.notify_one();
+        }
+        Poll::Ready(())
+    }
+
+    //whether a notify_waiters or permit is for us
+    fn attempt(&self) -> bool {
+        self.notify.generation.load(Ordering::SeqCst) != self.generation || self.notify.take_permit()
+    }
+}
+
+impl<'a> Future for Notified<'a> {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.attempt() {
+            return self.finish();
+        }
+        if self.registration.register_or_take_wakeup(cx.waker()) {
+            //notify_one picked us
+            return Poll::Ready(());
+        }
+        if self.attempt() {
+            return self.finish();
+        }
+        Poll::Pending
+    }
+}
+
+impl<'a> Drop for Notified<'a> {
+    fn drop(&mut self) {
+        //a notify_one wakeup we never saw shouldn't be lost; one from notify_waiters was for everyone anyway
+        if self.registration.take_wakeup() && self.notify.generation.load(Ordering::SeqCst) == self.generation {
+            self.notify.notify_one();
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Owned guards for locks shared with `Arc`. */
+
+use alloc::sync::Arc;
+use core::fmt::{Debug, Display};
+use core::marker::PhantomData;
+use crate::{AtomicLock, StateWord};
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    Locks the lock and accesses the data if available, returning a guard that keeps the `Arc` alive.
+
+    The guard borrows nothing, so it can be stored in long-lived structs, or held across an `.await`
+    and sent to another thread.  [OwnedGuard::map] narrows it to a part of the data, still owned.
+
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock_arc(self: &Arc<Self>) -> Option<OwnedGuard<T, S>> {
+        let guard = self.lock()?;
+        //OwnedGuard unlocks instead
+        core::mem::forget(guard);
+        Some(OwnedGuard { lock: self.clone(), _marker: PhantomData })
+    }
+}
+
+/**
+An owned guard for an [AtomicLock] in an `Arc`, from [AtomicLock::lock_arc].
+
+Unlocks when dropped.
+*/
+#[must_use]
+#[clippy::has_significant_drop]
+pub struct OwnedGuard<T, S: StateWord = crate::State> {
+    lock: Arc<AtomicLock<T, S>>,
+    //we hand out &mut T; keeps us from being Sync for any T, as the Arc alone would be
+    _marker: PhantomData<*mut T>,
+}
+
+impl<T, S: StateWord> OwnedGuard<T, S> {
+    /**
+    The `Arc` this guard keeps alive.
+
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    */
+    pub fn arc(guard: &Self) -> &Arc<AtomicLock<T, S>> {
+        &guard.lock
+    }
+
+    /**
+    Makes a new [OwnedMappedGuard] for a component of the locked data.
+
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    Use it as `OwnedGuard::map(guard, |data| &mut data.field)`.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn map<U, F>(mut guard: Self, f: F) -> OwnedMappedGuard<T, U, S> where F: FnOnce(&mut T) -> &mut U {
+        let data: *mut U = f(&mut guard);
+        OwnedMappedGuard { lock: Self::into_arc(guard), data }
+    }
+
+    //gives up the guard without unlocking
+    fn into_arc(guard: Self) -> Arc<AtomicLock<T, S>> {
+        let guard = core::mem::ManuallyDrop::new(guard);
+        //Safety: the guard is never used or dropped again
+        unsafe { core::ptr::read(&guard.lock) }
+    }
+}
+
+impl<T, S: StateWord> Drop for OwnedGuard<T, S> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+impl<T, S: StateWord> core::ops::Deref for OwnedGuard<T, S> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T, S: StateWord> core::ops::DerefMut for OwnedGuard<T, S> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T, S: StateWord> AsRef<T> for OwnedGuard<T, S> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T, S: StateWord> AsMut<T> for OwnedGuard<T, S> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: Debug, S: StateWord> Debug for OwnedGuard<T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OwnedGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<T: Display, S: StateWord> Display for OwnedGuard<T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/**
+An owned guard for some part of the data inside an [AtomicLock] in an `Arc`.
+
+Created by [OwnedGuard::map].  Keeps the `Arc` alive, and unlocks the lock when dropped.
+*/
+#[must_use]
+#[clippy::has_significant_drop]
+pub struct OwnedMappedGuard<T, U, S: StateWord = crate::State> {
+    lock: Arc<AtomicLock<T, S>>,
+    //points into the Arc's allocation, which doesn't move
+    data: *mut U,
+}
+
+impl<T, U, S: StateWord> OwnedMappedGuard<T, U, S> {
+    /**
+    Projects the guard further, onto a component of the already-mapped data.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn map<V, F>(mut guard: Self, f: F) -> OwnedMappedGuard<T, V, S> where F: FnOnce(&mut U) -> &mut V {
+        let data: *mut V = f(&mut guard);
+        let guard = core::mem::ManuallyDrop::new(guard);
+        //Safety: the guard is never used or dropped again
+        let lock = unsafe { core::ptr::read(&guard.lock) };
+        OwnedMappedGuard { lock, data }
+    }
+
+    /**
+    The `Arc` this guard keeps alive.
+
+    This is an associated function, so it doesn't conflict with methods on `U`.
+    */
+    pub fn arc(guard: &Self) -> &Arc<AtomicLock<T, S>> {
+        &guard.lock
+    }
+}
+
+impl<T, U, S: StateWord> Drop for OwnedMappedGuard<T, U, S> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+impl<T, U, S: StateWord> core::ops::Deref for OwnedMappedGuard<T, U, S> {
+    type Target = U;
+    fn deref(&self) -> &U {
+        unsafe { &*self.data }
+    }
+}
+
+impl<T, U, S: StateWord> core::ops::DerefMut for OwnedMappedGuard<T, U, S> {
+    fn deref_mut(&mut self) -> &mut U {
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<T, U, S: StateWord> AsRef<U> for OwnedMappedGuard<T, U, S> {
+    fn as_ref(&self) -> &U {
+        self
+    }
+}
+
+impl<T, U, S: StateWord> AsMut<U> for OwnedMappedGuard<T, U, S> {
+    fn as_mut(&mut self) -> &mut U {
+        self
+    }
+}
+
+impl<T, U: Debug, S: StateWord> Debug for OwnedMappedGuard<T, U, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OwnedMappedGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<T, U: Display, S: StateWord> Display for OwnedMappedGuard<T, U, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+//the last Arc may go with the guard, dropping the T wherever the guard is
+unsafe impl<T: Send, S: StateWord + Send + Sync> Send for OwnedGuard<T, S> {}
+unsafe impl<T: Send + Sync, S: StateWord + Send + Sync> Sync for OwnedGuard<T, S> {}
+//as MappedGuard, plus the Arc
+unsafe impl<T: Send, U: Send, S: StateWord + Send + Sync> Send for OwnedMappedGuard<T, U, S> {}
+unsafe impl<T: Send, U: Sync, S: StateWord + Send + Sync> Sync for OwnedMappedGuard<T, U, S> {}
@@ -0,0 +1,24 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Cache-line padding for locks that sit next to each other in memory. */
+
+/**
+Pads and aligns `T` to a cache line, so neighbouring values don't false-share.
+
+128 bytes covers the adjacent-line prefetcher on x86_64 and the line size on Apple silicon.
+*/
+#[derive(Debug, Default)]
+#[repr(align(128))]
+pub(crate) struct CachePadded<T>(pub(crate) T);
+
+impl<T> core::ops::Deref for CachePadded<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> core::ops::DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,94 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! The `futex` [Parker] backend: the OS's native wait-on-address. */
+
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::time::Instant;
+use crate::futex;
+
+/**
+Threads waiting on an epoch counter with the native wait-on-address primitive.
+
+Every unpark bumps the epoch, so a thread that read the old epoch either sees the change or is woken.
+*/
+#[derive(Debug)]
+pub(crate) struct Parker {
+    epoch: AtomicU32,
+    //number of threads that may be waiting, so unparking can skip the syscall
+    parked: AtomicUsize,
+}
+
+impl Parker {
+    pub(crate) const fn new() -> Self {
+        Parker {
+            epoch: AtomicU32::new(0),
+            parked: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn park_until<R>(&self, mut attempt: impl FnMut() -> Option<R>, blocked: impl Fn() -> bool) -> R {
+        loop {
+            if let Some(r) = attempt() {
+                return r;
+            }
+            self.parked.fetch_add(1, Ordering::Relaxed);
+            //pairs with the fence in unpark_one: either we see their release, or they see our count
+            core::sync::atomic::fence(Ordering::SeqCst);
+            //acquire pairs with the release in unpark_one, so if we see their epoch we see their unlock too
+            let epoch = self.epoch.load(Ordering::Acquire);
+            if blocked() {
+                futex::wait(&self.epoch, epoch);
+            }
+            self.parked.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn park_until_timeout<R>(&self, mut attempt: impl FnMut() -> Option<R>, blocked: impl Fn() -> bool, deadline: Instant) -> Option<R> {
+        loop {
+            if let Some(r) = attempt() {
+                return Some(r);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            self.parked.fetch_add(1, Ordering::Relaxed);
+            //as in park_until
+            core::sync::atomic::fence(Ordering::SeqCst);
+            let epoch = self.epoch.load(Ordering::Acquire);
+            if blocked() {
+                futex::wait_timeout(&self.epoch, epoch, deadline - now);
+            }
+            self.parked.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn unpark_one(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if self.parked.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        self.epoch.fetch_add(1, Ordering::Release);
+        futex::wake_one(&self.epoch);
+    }
+
+    pub(crate) fn unpark_handoff(&self, handoff: impl FnOnce()) -> bool {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        //a counted thread doesn't leave park_until until an attempt succeeds
+        if self.parked.load(Ordering::Relaxed) == 0 {
+            return false;
+        }
+        handoff();
+        self.epoch.fetch_add(1, Ordering::Release);
+        futex::wake_one(&self.epoch);
+        true
+    }
+
+    pub(crate) fn unpark_all(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if self.parked.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        self.epoch.fetch_add(1, Ordering::Release);
+        futex::wake_all(&self.epoch);
+    }
+}
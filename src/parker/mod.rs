@@ -0,0 +1,34 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Parking threads that are waiting on a lock.
+
+There are three backends, in order of preference:
+* With the `futex` feature on Linux, Windows, macOS and wasm32 with atomics, we wait on an address with the native OS primitive.
+* With the `parking` feature, we use `parking_lot_core`'s global parking lot, keyed by the address of the [Parker].
+* Otherwise, we keep our own queue of threads.
+
+All backends have the same contract:
+* [Parker::park_until] parks the current thread until `attempt` succeeds.  `blocked` reports whether
+  `attempt` would currently fail; it is used to decide whether parking is still necessary.
+* [Parker::park_until_timeout] is the same, but gives up at `deadline`, returning None.
+  A thread that gives up won't attempt again, so don't use it with [Parker::unpark_handoff].
+* [Parker::unpark_one] wakes one parked thread.  It must be called *after* whatever makes `attempt` succeed.
+* [Parker::unpark_all] wakes every parked thread, with the same caveat.
+* [Parker::unpark_handoff] wakes one parked thread *instead* of making `attempt` succeed for everyone.
+  If there's a thread that will call `attempt` again, it calls `handoff` first, so that thread's `attempt`
+  can succeed, and returns true.  Otherwise it returns false, and the caller releases and calls
+  [Parker::unpark_one] as usual.
+*/
+
+#[cfg(all(feature = "futex", any(target_os = "linux", target_os = "windows", target_os = "macos", all(target_arch = "wasm32", target_feature = "atomics"))))]
+mod futex;
+#[cfg(all(feature = "parking", not(all(feature = "futex", any(target_os = "linux", target_os = "windows", target_os = "macos", all(target_arch = "wasm32", target_feature = "atomics"))))))]
+mod parking;
+#[cfg(not(any(feature = "parking", all(feature = "futex", any(target_os = "linux", target_os = "windows", target_os = "macos", all(target_arch = "wasm32", target_feature = "atomics"))))))]
+mod queue;
+
+#[cfg(all(feature = "futex", any(target_os = "linux", target_os = "windows", target_os = "macos", all(target_arch = "wasm32", target_feature = "atomics"))))]
+pub(crate) use futex::Parker;
+#[cfg(all(feature = "parking", not(all(feature = "futex", any(target_os = "linux", target_os = "windows", target_os = "macos", all(target_arch = "wasm32", target_feature = "atomics"))))))]
+pub(crate) use parking::Parker;
+#[cfg(not(any(feature = "parking", all(feature = "futex", any(target_os = "linux", target_os = "windows", target_os = "macos", all(target_arch = "wasm32", target_feature = "atomics"))))))]
+pub(crate) use queue::Parker;
@@ -0,0 +1,120 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! The `parking` [Parker] backend: `parking_lot_core`'s global parking lot. */
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/**
+Threads parked in `parking_lot_core`'s global parking lot.
+*/
+#[derive(Debug)]
+pub(crate) struct Parker {
+    //whether anyone may be parked on us, so unparking can skip the parking lot
+    parked: AtomicBool,
+}
+
+impl Parker {
+    pub(crate) const fn new() -> Self {
+        Parker { parked: AtomicBool::new(false) }
+    }
+
+    fn key(&self) -> usize {
+        self as *const Parker as usize
+    }
+
+    pub(crate) fn park_until<R>(&self, mut attempt: impl FnMut() -> Option<R>, blocked: impl Fn() -> bool) -> R {
+        loop {
+            if let Some(r) = attempt() {
+                return r;
+            }
+            let validate = || {
+                self.parked.store(true, Ordering::Relaxed);
+                //pairs with the fence in unpark_one: either we see their release, or they see our flag
+                core::sync::atomic::fence(Ordering::SeqCst);
+                blocked()
+            };
+            //Safety: we don't panic or call into parking_lot_core from any of the callbacks
+            unsafe {
+                parking_lot_core::park(self.key(), validate, || {}, |_, _| {}, parking_lot_core::DEFAULT_PARK_TOKEN, None);
+            }
+        }
+    }
+
+    pub(crate) fn park_until_timeout<R>(&self, mut attempt: impl FnMut() -> Option<R>, blocked: impl Fn() -> bool, deadline: Instant) -> Option<R> {
+        loop {
+            if let Some(r) = attempt() {
+                return Some(r);
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            //as in park_until
+            let validate = || {
+                self.parked.store(true, Ordering::Relaxed);
+                core::sync::atomic::fence(Ordering::SeqCst);
+                blocked()
+            };
+            //Safety: we don't panic or call into parking_lot_core from any of the callbacks
+            unsafe {
+                parking_lot_core::park(self.key(), validate, || {}, |_, _| {}, parking_lot_core::DEFAULT_PARK_TOKEN, Some(deadline));
+            }
+        }
+    }
+
+    pub(crate) fn unpark_one(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if !self.parked.load(Ordering::Relaxed) {
+            return;
+        }
+        let callback = |result: parking_lot_core::UnparkResult| {
+            if !result.have_more_threads {
+                self.parked.store(false, Ordering::Relaxed);
+            }
+            parking_lot_core::DEFAULT_UNPARK_TOKEN
+        };
+        //Safety: the callback doesn't panic or call into parking_lot_core
+        unsafe {
+            parking_lot_core::unpark_one(self.key(), callback);
+        }
+    }
+
+    pub(crate) fn unpark_handoff(&self, handoff: impl FnOnce()) -> bool {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        //`parked` may be stale, so only the parking lot knows whether anyone is really there
+        if !self.parked.load(Ordering::Relaxed) {
+            return false;
+        }
+        let mut handoff = Some(handoff);
+        let mut handed_off = false;
+        let callback = |result: parking_lot_core::UnparkResult| {
+            if !result.have_more_threads {
+                self.parked.store(false, Ordering::Relaxed);
+            }
+            //runs before the thread wakes, so it sees the handoff
+            if result.unparked_threads > 0 {
+                if let Some(handoff) = handoff.take() {
+                    handoff();
+                }
+                handed_off = true;
+            }
+            parking_lot_core::DEFAULT_UNPARK_TOKEN
+        };
+        //Safety: the callback doesn't panic or call into parking_lot_core
+        unsafe {
+            parking_lot_core::unpark_one(self.key(), callback);
+        }
+        handed_off
+    }
+
+    pub(crate) fn unpark_all(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if !self.parked.load(Ordering::Relaxed) {
+            return;
+        }
+        //we leave `parked` set; the next unpark_one will clear it
+        //Safety: we use no callbacks
+        unsafe {
+            parking_lot_core::unpark_all(self.key(), parking_lot_core::DEFAULT_UNPARK_TOKEN);
+        }
+    }
+}
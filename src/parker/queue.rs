@@ -0,0 +1,145 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! The default [Parker] backend: our own queue of threads. */
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, Thread};
+use std::time::Instant;
+use crate::{AtomicLock, Guard};
+
+/**
+A queue of parked threads.
+*/
+#[derive(Debug)]
+pub(crate) struct Parker {
+    threads: AtomicLock<Vec<Thread>>,
+    //mirrors threads.len(), so unparking can skip the queue when nobody is parked
+    parked: AtomicUsize,
+}
+
+impl Parker {
+    pub(crate) const fn new() -> Self {
+        Parker {
+            threads: AtomicLock::new(Vec::new()),
+            parked: AtomicUsize::new(0),
+        }
+    }
+
+    fn queue(&self) -> Guard<'_, Vec<Thread>> {
+        loop {
+            if let Some(guard) = self.threads.lock() {
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    pub(crate) fn park_until<R>(&self, mut attempt: impl FnMut() -> Option<R>, _blocked: impl Fn() -> bool) -> R {
+        let current = thread::current();
+        loop {
+            {
+                let mut queue = self.queue();
+                queue.push(current.clone());
+                self.parked.store(queue.len(), Ordering::Relaxed);
+            }
+            //pairs with the fence in unpark_one: either we see their release, or they see our entry
+            core::sync::atomic::fence(Ordering::SeqCst);
+            if let Some(r) = attempt() {
+                self.remove(&current);
+                return r;
+            }
+            thread::park();
+            //we may have woken spuriously, with our entry still queued
+            self.remove(&current);
+            if let Some(r) = attempt() {
+                return r;
+            }
+        }
+    }
+
+    pub(crate) fn park_until_timeout<R>(&self, mut attempt: impl FnMut() -> Option<R>, _blocked: impl Fn() -> bool, deadline: Instant) -> Option<R> {
+        let current = thread::current();
+        loop {
+            {
+                let mut queue = self.queue();
+                queue.push(current.clone());
+                self.parked.store(queue.len(), Ordering::Relaxed);
+            }
+            //as in park_until
+            core::sync::atomic::fence(Ordering::SeqCst);
+            if let Some(r) = attempt() {
+                self.remove(&current);
+                return Some(r);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                self.remove(&current);
+                return None;
+            }
+            thread::park_timeout(deadline - now);
+            self.remove(&current);
+            if let Some(r) = attempt() {
+                return Some(r);
+            }
+        }
+    }
+
+    fn remove(&self, current: &Thread) {
+        let mut queue = self.queue();
+        if let Some(position) = queue.iter().position(|t| t.id() == current.id()) {
+            queue.remove(position);
+            self.parked.store(queue.len(), Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn unpark_one(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if self.parked.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let thread = {
+            let mut queue = self.queue();
+            let thread = if queue.is_empty() { None } else { Some(queue.remove(0)) };
+            self.parked.store(queue.len(), Ordering::Relaxed);
+            thread
+        };
+        if let Some(thread) = thread {
+            thread.unpark();
+        }
+    }
+
+    pub(crate) fn unpark_handoff(&self, handoff: impl FnOnce()) -> bool {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if self.parked.load(Ordering::Relaxed) == 0 {
+            return false;
+        }
+        let thread = {
+            let mut queue = self.queue();
+            if queue.is_empty() {
+                return false;
+            }
+            //a queued thread hasn't succeeded yet, so it will attempt again once woken
+            let thread = queue.remove(0);
+            self.parked.store(queue.len(), Ordering::Relaxed);
+            thread
+        };
+        handoff();
+        thread.unpark();
+        true
+    }
+
+    pub(crate) fn unpark_all(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if self.parked.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let threads = {
+            let mut queue = self.queue();
+            self.parked.store(0, Ordering::Relaxed);
+            core::mem::take(&mut *queue)
+        };
+        for thread in threads {
+            thread.unpark();
+        }
+    }
+}
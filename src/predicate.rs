@@ -0,0 +1,44 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locking conditional on the data. */
+
+use crate::{AtomicLock, Guard, StateWord};
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    Locks the lock if available and `pred` holds for the data.
+
+    If the lock is unavailable, or `pred` returns false, the lock is left unlocked and this returns None.
+    If `pred` panics, the lock is unlocked.
+    */
+    pub fn lock_if<F>(&self, pred: F) -> Option<Guard<'_, T, S>> where F: FnOnce(&T) -> bool {
+        let guard = self.lock()?;
+        if pred(&guard) {
+            Some(guard)
+        } else {
+            None
+        }
+    }
+
+    /**
+    Spins until `pred` holds for the data, and returns the guard once it does.
+
+    Each attempt locks, checks `pred`, and unlocks again if it's false, so other threads
+    can get in and make it true.  This is a poor man's condition variable, for when there's no OS to sleep on.
+
+    With a `budget`, gives up and returns None after that many attempts, including attempts
+    that found the lock busy.  With None, spins forever.
+    */
+    pub fn spin_wait_for<F>(&self, mut pred: F, budget: Option<usize>) -> Option<Guard<'_, T, S>> where F: FnMut(&T) -> bool {
+        let mut attempts = 0;
+        loop {
+            if budget.map_or(false, |budget| attempts >= budget) {
+                return None;
+            }
+            attempts += 1;
+            if let Some(guard) = self.lock_if(&mut pred) {
+                return Some(guard);
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A lock with two priority tiers. */
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+A lock where high-priority acquirers can ask low-priority ones to back off.
+
+A high-priority acquirer calls [PriorityLock::intent] to announce that it wants the lock.
+While any [Intent] is alive, [PriorityLock::lock] returns None even if the lock is available,
+so the high-priority side wins the next time the lock is released.
+
+This is a voluntary scheme, not priority inheritance: a low-priority holder who already has
+the lock keeps it until they drop the guard.
+*/
+#[derive(Debug, Default)]
+pub struct PriorityLock<T> {
+    lock: AtomicLock<T>,
+    intents: AtomicUsize,
+}
+
+impl<T> PriorityLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        PriorityLock {
+            lock: AtomicLock::new(data),
+            intents: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+    Low-priority lock.
+
+    Returns None if the lock is unavailable, or if a high-priority acquirer has announced an [Intent].
+    */
+    pub fn lock(&self) -> Option<Guard<'_, T>> {
+        if self.intents.load(Ordering::Relaxed) != 0 {
+            return None;
+        }
+        self.lock.lock()
+    }
+
+    /**
+    High-priority lock.
+
+    Ignores any announced intent.  Returns None only if the lock is unavailable.
+    */
+    pub fn lock_high(&self) -> Option<Guard<'_, T>> {
+        self.lock.lock()
+    }
+
+    /**
+    Announces that a high-priority acquirer wants the lock.
+
+    Low-priority calls to [PriorityLock::lock] fail until the returned [Intent] is dropped.
+    */
+    pub fn intent(&self) -> Intent<'_, T> {
+        self.intents.fetch_add(1, Ordering::Relaxed);
+        Intent { lock: self }
+    }
+
+    /**
+    Whether any high-priority acquirer has announced an [Intent].
+    */
+    pub fn has_intent(&self) -> bool {
+        self.intents.load(Ordering::Relaxed) != 0
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T> From<T> for PriorityLock<T> {
+    fn from(data: T) -> Self {
+        PriorityLock::new(data)
+    }
+}
+
+/**
+A high-priority claim on a [PriorityLock].
+
+Keeps low-priority acquirers away while alive.
+*/
+#[derive(Debug)]
+#[must_use]
+pub struct Intent<'a, T> {
+    lock: &'a PriorityLock<T>,
+}
+
+impl<'a, T> Intent<'a, T> {
+    /**
+    Locks the lock at high priority, if available.
+
+    The intent stays announced until it is dropped, so you can retry.
+    */
+    pub fn lock(&self) -> Option<Guard<'a, T>> {
+        self.lock.lock_high()
+    }
+}
+
+impl<'a, T> Drop for Intent<'a, T> {
+    fn drop(&mut self) {
+        self.lock.intents.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,134 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Profiler zones for lock wait and hold times.
+
+With the `profiling` feature, locks report when a thread starts and stops waiting for a lock,
+and when it takes and releases one, to the [ProfilerHooks] you install with [set_profiler].
+Forward those to Tracy, puffin or whatever draws your frame timeline, and contention shows up there.
+
+This crate doesn't depend on any profiler; the hooks are plain function pointers.
+Until a profiler is installed, each report costs one atomic load.
+*/
+
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/**
+Functions called as locks are waited for, taken and released.
+
+Each gets the address of the lock, which identifies it in your profiler.
+
+Hold zones cover every time an [AtomicLock](crate::AtomicLock) is held, including the locks
+inside other types in this crate.  Wait zones cover the functions that wait on one, like
+[AtomicLock::lock_timeout](crate::AtomicLock::lock_timeout), and only start once the first
+attempt has failed.  A wait zone always closes before the hold zone opens, so the zones nest.
+
+The [Spin](crate::Spin) family of adaptors works on any [TryLock](crate::TryLock), so it can't do
+that, and reports only hold zones.
+
+Hooks run while locks are held, so they must not take the lock they're told about.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct ProfilerHooks {
+    /**
+    A thread started waiting for the lock.
+    */
+    pub wait_begin: fn(lock: *const ()),
+    /**
+    A thread stopped waiting for the lock, whether or not it got it.
+    */
+    pub wait_end: fn(lock: *const ()),
+    /**
+    A thread took the lock.
+    */
+    pub hold_begin: fn(lock: *const ()),
+    /**
+    A thread released the lock.
+    */
+    pub hold_end: fn(lock: *const ()),
+}
+
+#[cfg(target_has_atomic = "ptr")]
+static PROFILER: AtomicPtr<ProfilerHooks> = AtomicPtr::new(core::ptr::null_mut());
+
+/**
+Installs `hooks` as the profiler for every lock, or removes the profiler with None.
+
+Zones that are open when the profiler changes may be reported to only one of the profilers.
+*/
+#[cfg(target_has_atomic = "ptr")]
+pub fn set_profiler(hooks: Option<&'static ProfilerHooks>) {
+    let ptr = hooks.map_or(core::ptr::null_mut(), |hooks| hooks as *const ProfilerHooks as *mut ProfilerHooks);
+    PROFILER.store(ptr, Ordering::Release);
+}
+
+#[inline]
+fn profiler() -> Option<&'static ProfilerHooks> {
+    #[cfg(target_has_atomic = "ptr")]
+    {
+        //Safety: only ever set from a &'static
+        unsafe { PROFILER.load(Ordering::Acquire).as_ref() }
+    }
+    #[cfg(not(target_has_atomic = "ptr"))]
+    {
+        None
+    }
+}
+
+#[inline]
+pub(crate) fn hold_begin(lock: *const ()) {
+    #[cfg(feature = "thread-stats")]
+    crate::thread_stats::hold_begin(lock);
+    #[cfg(feature = "holder-backtrace")]
+    crate::holder::hold_begin(lock);
+    if let Some(hooks) = profiler() {
+        (hooks.hold_begin)(lock);
+    }
+}
+
+#[inline]
+pub(crate) fn hold_end(lock: *const ()) {
+    #[cfg(feature = "thread-stats")]
+    crate::thread_stats::hold_end(lock);
+    #[cfg(feature = "holder-backtrace")]
+    crate::holder::hold_end(lock);
+    if let Some(hooks) = profiler() {
+        (hooks.hold_end)(lock);
+    }
+}
+
+/**
+An open wait zone, closed when dropped.
+*/
+pub(crate) struct WaitZone {
+    lock: *const (),
+}
+
+impl WaitZone {
+    #[inline]
+    pub(crate) fn new<L>(lock: &L) -> Self {
+        let lock = lock as *const L as *const ();
+        if let Some(hooks) = profiler() {
+            (hooks.wait_begin)(lock);
+        }
+        WaitZone { lock }
+    }
+
+    /**
+    Closes the zone and opens a hold zone, for a wait that got the lock without telling the profiler.
+    */
+    #[inline]
+    pub(crate) fn acquired(self) {
+        let lock = self.lock;
+        drop(self);
+        hold_begin(lock);
+    }
+}
+
+impl Drop for WaitZone {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(hooks) = profiler() {
+            (hooks.wait_end)(self.lock);
+        }
+    }
+}
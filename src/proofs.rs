@@ -0,0 +1,67 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! [Kani](https://github.com/model-checking/kani) proof harnesses.
+
+Run with `cargo kani`.  Kani has no threads, so these prove the single-threaded state machine:
+whatever sequence of operations you perform, the lock is held exactly when a guard is alive.
+*/
+
+use crate::{AtomicLock, Guard};
+
+#[kani::proof]
+fn mutual_exclusion() {
+    let lock = AtomicLock::new(kani::any::<u8>());
+    let guard = lock.lock();
+    assert!(guard.is_some());
+    //no second guard while the first is alive
+    assert!(lock.lock().is_none());
+    drop(guard);
+    assert!(lock.lock().is_some());
+}
+
+#[kani::proof]
+#[kani::unwind(6)]
+fn held_exactly_while_guard_alive() {
+    let lock = AtomicLock::new(0u8);
+    let mut guard: Option<Guard<'_, u8>> = None;
+    for _ in 0..5 {
+        if kani::any() {
+            match lock.lock() {
+                Some(new) => {
+                    assert!(guard.is_none());
+                    guard = Some(new);
+                }
+                None => assert!(guard.is_some()),
+            }
+        } else {
+            guard = None;
+        }
+        assert_eq!(lock.is_locked(), guard.is_some());
+    }
+}
+
+#[kani::proof]
+fn writes_are_visible_after_unlock() {
+    let value: u8 = kani::any();
+    let lock = AtomicLock::new(0u8);
+    *lock.lock().unwrap() = value;
+    assert_eq!(*lock.lock().unwrap(), value);
+    assert_eq!(lock.into_inner(), value);
+}
+
+#[kani::proof]
+fn mapped_guard_unlocks() {
+    let lock = AtomicLock::new((kani::any::<u8>(), kani::any::<u8>()));
+    let mapped = Guard::map(lock.lock().unwrap(), |data| &mut data.1);
+    assert!(lock.is_locked());
+    drop(mapped);
+    assert!(!lock.is_locked());
+}
+
+#[cfg(any(debug_assertions, feature = "strict"))]
+#[kani::proof]
+#[kani::should_panic]
+fn no_double_unlock() {
+    let lock = AtomicLock::new(0u8);
+    drop(lock.lock().unwrap());
+    lock.unlock();
+}
@@ -0,0 +1,134 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A bounded queue with one lock for each end. */
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::AtomicLock;
+use crate::padded::CachePadded;
+
+/**
+A bounded multi-producer, multi-consumer queue of up to `N` values, without allocating.
+
+The tail is guarded by one [AtomicLock] and the head by another, so a push and a pop don't
+contend with each other, only with other pushes and pops.  Like the lock itself, each
+operation is a single attempt: [LockedQueue::try_push] and [LockedQueue::try_pop] return
+right away if their end is busy, and you decide whether to retry.
+*/
+pub struct LockedQueue<T, const N: usize> {
+    //index of the next slot to pop
+    head: CachePadded<AtomicLock<usize>>,
+    //index of the next slot to push
+    tail: CachePadded<AtomicLock<usize>>,
+    //slots that are pushed and not yet popped.  Only a pusher increments it and only a popper
+    //decrements it, so with one of each at a time, it works as it would for a single producer and consumer.
+    len: AtomicUsize,
+    slots: UnsafeCell<MaybeUninit<[T; N]>>,
+}
+
+impl<T, const N: usize> LockedQueue<T, N> {
+    /**
+    Creates a new, empty queue.
+    */
+    pub const fn new() -> Self {
+        LockedQueue {
+            head: CachePadded(AtomicLock::new(0)),
+            tail: CachePadded(AtomicLock::new(0)),
+            len: AtomicUsize::new(0),
+            slots: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    fn slot(&self, index: usize) -> *mut T {
+        //in bounds, because indices wrap at N
+        unsafe { (self.slots.get() as *mut T).add(index) }
+    }
+
+    /**
+    Pushes `value` onto the back of the queue.
+
+    If the queue is full, or another push is in progress, gives the value back.
+    */
+    pub fn try_push(&self, value: T) -> Result<(), T> {
+        let Some(mut tail) = self.tail.lock() else { return Err(value) };
+        //pairs with the Release in try_pop, so the slot we reuse has been read out
+        if self.len.load(Ordering::Acquire) == N {
+            return Err(value);
+        }
+        //we hold the tail, and the slot is free, so no one else can touch it
+        unsafe { self.slot(*tail).write(value) };
+        *tail = if *tail + 1 == N { 0 } else { *tail + 1 };
+        self.len.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+
+    /**
+    Pops the value at the front of the queue.
+
+    Returns None if the queue is empty, or another pop is in progress.
+    */
+    pub fn try_pop(&self) -> Option<T> {
+        let mut head = self.head.lock()?;
+        //pairs with the Release in try_push, so the slot we read has been written
+        if self.len.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+        //we hold the head, and the slot is full, so no one else can touch it
+        let value = unsafe { self.slot(*head).read() };
+        *head = if *head + 1 == N { 0 } else { *head + 1 };
+        self.len.fetch_sub(1, Ordering::Release);
+        Some(value)
+    }
+
+    /**
+    The number of values in the queue.
+
+    Pushes and pops may change it right after you look.
+    */
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /**
+    Whether the queue is empty.
+
+    Pushes and pops may change it right after you look.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+    The most values the queue can hold.
+    */
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> Drop for LockedQueue<T, N> {
+    fn drop(&mut self) {
+        let mut head = *self.head.0.data.get_mut();
+        for _ in 0..*self.len.get_mut() {
+            unsafe { self.slot(head).drop_in_place() };
+            head = if head + 1 == N { 0 } else { head + 1 };
+        }
+    }
+}
+
+unsafe impl<T: Send, const N: usize> Send for LockedQueue<T, N> {}
+unsafe impl<T: Send, const N: usize> Sync for LockedQueue<T, N> {}
+
+//boilerplate
+
+impl<T, const N: usize> Default for LockedQueue<T, N> {
+    fn default() -> Self {
+        LockedQueue::new()
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for LockedQueue<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LockedQueue").field("len", &self.len()).field("capacity", &N).finish()
+    }
+}
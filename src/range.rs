@@ -0,0 +1,171 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locking disjoint ranges of a slice. */
+
+use core::marker::PhantomData;
+use core::ops::Range;
+use crate::{AtomicLock, Guard};
+
+/**
+Locks disjoint ranges of a borrowed slice independently.
+
+[RangeLock::try_lock_range] succeeds only if no overlapping range is held, so threads can write to
+different regions of one buffer at the same time.
+
+At most `MAX_RANGES` ranges can be held at once; beyond that, locking fails as if the range overlapped.
+*/
+pub struct RangeLock<'a, T, const MAX_RANGES: usize = 8> {
+    data: *mut T,
+    len: usize,
+    //the held ranges, by slot; this lock is only ever held for a few comparisons
+    held: AtomicLock<[Option<Range<usize>>; MAX_RANGES]>,
+    _marker: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T, const MAX_RANGES: usize> RangeLock<'a, T, MAX_RANGES> {
+    /**
+    Creates a new lock over `data`, with nothing locked.
+    */
+    pub fn new(data: &'a mut [T]) -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const FREE: Option<Range<usize>> = None;
+        RangeLock {
+            data: data.as_mut_ptr(),
+            len: data.len(),
+            held: AtomicLock::new([FREE; MAX_RANGES]),
+            _marker: PhantomData,
+        }
+    }
+
+    /**
+    The length of the whole slice.
+    */
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /**
+    Whether the whole slice is empty.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /**
+    Locks `range` of the slice, if no overlapping range is held.
+    Will return None if one is, or if `MAX_RANGES` ranges are already held.
+
+    An empty range overlaps nothing, so it always succeeds.
+
+    # Panics
+    Panics if `range` is out of bounds or decreasing, like slicing would.
+    */
+    pub fn try_lock_range(&self, range: Range<usize>) -> Option<RangeGuard<'_, T>> {
+        assert!(range.start <= range.end, "range start {} is after end {}", range.start, range.end);
+        assert!(range.end <= self.len, "range end {} out of bounds for slice of length {}", range.end, self.len);
+        let unlock: &dyn UnlockSlot = self;
+        if range.start == range.end {
+            return Some(RangeGuard::new(unlock, None, self.data, range));
+        }
+        let mut held = self.held();
+        if held.iter().flatten().any(|other| other.start < range.end && range.start < other.end) {
+            return None;
+        }
+        let slot = held.iter().position(|r| r.is_none())?;
+        held[slot] = Some(range.clone());
+        Some(RangeGuard::new(unlock, Some(slot), self.data, range))
+    }
+
+    fn held(&self) -> Guard<'_, [Option<Range<usize>>; MAX_RANGES]> {
+        loop {
+            if let Some(guard) = self.held.lock() {
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+//lets RangeGuard release its slot without knowing MAX_RANGES
+trait UnlockSlot {
+    fn unlock_slot(&self, slot: usize);
+}
+
+impl<'a, T, const MAX_RANGES: usize> UnlockSlot for RangeLock<'a, T, MAX_RANGES> {
+    fn unlock_slot(&self, slot: usize) {
+        self.held()[slot] = None;
+    }
+}
+
+impl<'a, T, const MAX_RANGES: usize> core::fmt::Debug for RangeLock<'a, T, MAX_RANGES> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RangeLock")
+            .field("len", &self.len)
+            .field("held", &self.held)
+            .finish()
+    }
+}
+
+//guards on disjoint ranges hand out &mut [T] to different threads
+#[cfg(target_has_atomic = "8")]
+unsafe impl<'a, T: Send, const MAX_RANGES: usize> Sync for RangeLock<'a, T, MAX_RANGES> {}
+unsafe impl<'a, T: Send, const MAX_RANGES: usize> Send for RangeLock<'a, T, MAX_RANGES> {}
+
+/**
+A guard for one range of a [RangeLock].
+
+Derefs to the locked sub-slice.  Unlocks the range when dropped.
+*/
+#[must_use]
+pub struct RangeGuard<'a, T> {
+    lock: &'a dyn UnlockSlot,
+    slot: Option<usize>,
+    data: *mut T,
+    range: Range<usize>,
+}
+
+impl<'a, T> RangeGuard<'a, T> {
+    fn new(lock: &'a dyn UnlockSlot, slot: Option<usize>, data: *mut T, range: Range<usize>) -> Self {
+        RangeGuard { lock, slot, data, range }
+    }
+
+    /**
+    The range of the whole slice this guard holds.
+    */
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
+impl<'a, T> Drop for RangeGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.lock.unlock_slot(slot);
+        }
+    }
+}
+
+impl<'a, T> core::ops::Deref for RangeGuard<'a, T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.data.add(self.range.start), self.range.end - self.range.start) }
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for RangeGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.data.add(self.range.start), self.range.end - self.range.start) }
+    }
+}
+
+impl<'a, T> core::fmt::Debug for RangeGuard<'a, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RangeGuard")
+            .field("range", &self.range)
+            .field("data", &&**self)
+            .finish()
+    }
+}
+
+//behaves like &mut [T]
+unsafe impl<'a, T: Send> Send for RangeGuard<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for RangeGuard<'a, T> {}
@@ -0,0 +1,132 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! `lock_api` implementations.
+
+`lock_api`'s blocking methods spin, since there's nothing else a no_std crate can do.
+Use the `try_` methods if that's not what you want.
+*/
+
+use crate::AtomicLock;
+use crate::rwlock::RawAtomicRwLock;
+
+fn spin_until(mut attempt: impl FnMut() -> bool) {
+    while !attempt() {
+        core::hint::spin_loop();
+    }
+}
+
+/**
+An [AtomicLock] without any data, so it can be used with `lock_api::Mutex`.
+*/
+#[derive(Debug, Default)]
+pub struct RawAtomicLock {
+    lock: AtomicLock<()>,
+}
+
+impl RawAtomicLock {
+    /**
+    Creates a new, unlocked lock.
+    */
+    pub const fn new() -> Self {
+        RawAtomicLock { lock: AtomicLock::new(()) }
+    }
+}
+
+unsafe impl lock_api::RawMutex for RawAtomicLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawAtomicLock::new();
+    type GuardMarker = lock_api::GuardSend;
+
+    fn lock(&self) {
+        spin_until(|| lock_api::RawMutex::try_lock(self));
+    }
+
+    fn try_lock(&self) -> bool {
+        //lock_api keeps track of the lock itself
+        self.lock.lock().map(core::mem::forget).is_some()
+    }
+
+    unsafe fn unlock(&self) {
+        self.lock.unlock();
+    }
+
+    fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+}
+
+unsafe impl lock_api::RawRwLock for RawAtomicRwLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawAtomicRwLock::new();
+    type GuardMarker = lock_api::GuardSend;
+
+    fn lock_shared(&self) {
+        spin_until(|| self.try_read());
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        self.try_read()
+    }
+
+    unsafe fn unlock_shared(&self) {
+        self.unlock_read();
+    }
+
+    fn lock_exclusive(&self) {
+        spin_until(|| self.try_write());
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        self.try_write()
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        self.unlock_write();
+    }
+
+    fn is_locked(&self) -> bool {
+        self.load() != 0
+    }
+
+    fn is_locked_exclusive(&self) -> bool {
+        self.load() & crate::rwlock::WRITER != 0
+    }
+}
+
+unsafe impl lock_api::RawRwLockDowngrade for RawAtomicRwLock {
+    unsafe fn downgrade(&self) {
+        RawAtomicRwLock::downgrade(self);
+    }
+}
+
+unsafe impl lock_api::RawRwLockUpgrade for RawAtomicRwLock {
+    fn lock_upgradable(&self) {
+        spin_until(|| self.try_upgradable());
+    }
+
+    fn try_lock_upgradable(&self) -> bool {
+        self.try_upgradable()
+    }
+
+    unsafe fn unlock_upgradable(&self) {
+        RawAtomicRwLock::unlock_upgradable(self);
+    }
+
+    unsafe fn upgrade(&self) {
+        spin_until(|| RawAtomicRwLock::try_upgrade(self));
+    }
+
+    unsafe fn try_upgrade(&self) -> bool {
+        RawAtomicRwLock::try_upgrade(self)
+    }
+}
+
+unsafe impl lock_api::RawRwLockUpgradeDowngrade for RawAtomicRwLock {
+    unsafe fn downgrade_upgradable(&self) {
+        //UPGRADABLE -> one reader; nobody else can touch the upgradable bit meanwhile
+        self.upgradable_to_read();
+    }
+
+    unsafe fn downgrade_to_upgradable(&self) {
+        self.write_to_upgradable();
+    }
+}
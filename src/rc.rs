@@ -0,0 +1,91 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Owned guards for locks shared with `Rc`. */
+
+use alloc::rc::Rc;
+use core::fmt::{Debug, Display};
+use crate::{AtomicLock, StateWord};
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    Locks the lock and accesses the data if available, returning a guard that keeps the `Rc` alive.
+
+    The guard borrows nothing, so it can be stored in a struct or held across an `.await`, like an
+    `Arc`-based owned guard, but without atomic reference counts.  Like the `Rc`, it's not `Send`,
+    which suits single-threaded executors on wasm or thread-per-core runtimes.
+
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock_rc(self: &Rc<Self>) -> Option<RcGuard<T, S>> {
+        let guard = self.lock()?;
+        //RcGuard unlocks instead
+        core::mem::forget(guard);
+        Some(RcGuard { lock: self.clone() })
+    }
+}
+
+/**
+An owned guard for an [AtomicLock] in an `Rc`, from [AtomicLock::lock_rc].
+
+Unlocks when dropped.
+*/
+#[must_use]
+#[clippy::has_significant_drop]
+pub struct RcGuard<T, S: StateWord = crate::State> {
+    lock: Rc<AtomicLock<T, S>>,
+}
+
+impl<T, S: StateWord> RcGuard<T, S> {
+    /**
+    The `Rc` this guard keeps alive.
+
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    */
+    pub fn rc(guard: &Self) -> &Rc<AtomicLock<T, S>> {
+        &guard.lock
+    }
+}
+
+impl<T, S: StateWord> Drop for RcGuard<T, S> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
+
+impl<T, S: StateWord> core::ops::Deref for RcGuard<T, S> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T, S: StateWord> core::ops::DerefMut for RcGuard<T, S> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T, S: StateWord> AsRef<T> for RcGuard<T, S> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T, S: StateWord> AsMut<T> for RcGuard<T, S> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: Debug, S: StateWord> Debug for RcGuard<T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RcGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<T: Display, S: StateWord> Display for RcGuard<T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,61 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locked data that will only be read from now on. */
+
+use crate::{AtomicLock, StateWord};
+
+impl<T, S: StateWord> AtomicLock<T, S> {
+    /**
+    Consumes the lock, for data that will only be read from now on.
+
+    Reads through a [ReadOnly] are plain references, with no atomics at all.
+    This suits state that's built during startup, then shared (e.g. in an `Arc`) and only read.
+    */
+    pub fn into_read_only(self) -> ReadOnly<T> {
+        ReadOnly(self.into_inner())
+    }
+}
+
+/**
+Data that used to be in an [AtomicLock], and can now only be read.  From [AtomicLock::into_read_only].
+
+Derefs to the data.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ReadOnly<T>(T);
+
+impl<T> ReadOnly<T> {
+    /**
+    Returns the data.
+    */
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    /**
+    Puts the data back in a lock, so it can be written again.
+    */
+    pub fn into_lock(self) -> AtomicLock<T> {
+        AtomicLock::new(self.0)
+    }
+}
+
+impl<T> core::ops::Deref for ReadOnly<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for ReadOnly<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> core::fmt::Display for ReadOnly<T> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
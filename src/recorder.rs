@@ -0,0 +1,247 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A flight recorder of recent contention, for post-mortem debugging. */
+
+use core::panic::Location;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use crate::{AtomicLock, Clock, Guard};
+
+/**
+An [AtomicLock] that records its last `N` contention events in a fixed-size ring buffer.
+
+Each time [RecordedLock::lock] finds the lock busy, it records when, where it was called from,
+and where the holder locked it.  After a deadlock or a latency spike, dump
+[RecordedLock::contention_log] to see who was fighting over the lock.
+
+Recording never waits: if two threads are recording at the same moment, one event is dropped,
+and counted in [ContentionLog::dropped].
+*/
+pub struct RecordedLock<T, C, const N: usize = 16> {
+    lock: AtomicLock<T>,
+    clock: C,
+    holder: AtomicPtr<Location<'static>>,
+    ring: AtomicLock<Ring<N>>,
+    dropped: AtomicUsize,
+}
+
+struct Ring<const N: usize> {
+    events: [Option<ContentionEvent>; N],
+    next: usize,
+}
+
+impl<T, C: Clock, const N: usize> RecordedLock<T, C, N> {
+    /**
+    Creates a new lock, timestamping events with `clock`.
+    */
+    pub const fn new(data: T, clock: C) -> Self {
+        RecordedLock {
+            lock: AtomicLock::new(data),
+            clock,
+            holder: AtomicPtr::new(core::ptr::null_mut()),
+            ring: AtomicLock::new(Ring { events: [None; N], next: 0 }),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, records a contention event and returns None.
+    */
+    #[track_caller]
+    pub fn lock(&self) -> Option<Guard<'_, T>> {
+        let caller = Location::caller();
+        match self.lock.lock() {
+            Some(guard) => {
+                self.holder.store(caller as *const Location<'static> as *mut Location<'static>, Ordering::Relaxed);
+                Some(guard)
+            }
+            None => {
+                self.record(caller);
+                None
+            }
+        }
+    }
+
+    #[cold]
+    fn record(&self, waiter: &'static Location<'static>) {
+        //Safety: only ever set from a &'static
+        let holder = unsafe { self.holder.load(Ordering::Relaxed).as_ref() };
+        let event = ContentionEvent { time: self.clock.now(), waiter, holder };
+        match self.ring.lock() {
+            Some(mut ring) if N > 0 => {
+                let next = ring.next;
+                ring.events[next % N] = Some(event);
+                ring.next = next.wrapping_add(1);
+            }
+            //racing another recorder, or a dump
+            _ => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /**
+    Copies out the recorded events, oldest first.
+
+    Doesn't take the lock itself, so it works while the lock is stuck.
+    */
+    pub fn contention_log(&self) -> ContentionLog<N> {
+        let ring = loop {
+            if let Some(ring) = self.ring.lock() {
+                break ring;
+            }
+            core::hint::spin_loop();
+        };
+        let len = ring.next.min(N);
+        let mut events = [None; N];
+        for (i, event) in events.iter_mut().enumerate().take(len) {
+            *event = ring.events[(ring.next - len + i) % N];
+        }
+        ContentionLog { events, len, dropped: self.dropped.load(Ordering::Relaxed) }
+    }
+
+    /**
+    Whether the lock is currently held.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T, C, const N: usize> core::fmt::Debug for RecordedLock<T, C, N> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RecordedLock")
+            .field("lock", &self.lock)
+            .finish_non_exhaustive()
+    }
+}
+
+/**
+A time a [RecordedLock] was found busy.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct ContentionEvent {
+    time: u64,
+    waiter: &'static Location<'static>,
+    holder: Option<&'static Location<'static>>,
+}
+
+impl ContentionEvent {
+    /**
+    When it happened, in ticks of the lock's [Clock].
+    */
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /**
+    Where the caller who found the lock busy called [RecordedLock::lock].
+    */
+    pub fn waiter(&self) -> &'static Location<'static> {
+        self.waiter
+    }
+
+    /**
+    Where the holder took the lock, if it has ever been taken.
+
+    This is the most recent successful lock, so if the holder unlocked just before the attempt,
+    it may be out of date.
+    */
+    pub fn holder(&self) -> Option<&'static Location<'static>> {
+        self.holder
+    }
+}
+
+/**
+The recent contention of a [RecordedLock], from [RecordedLock::contention_log].
+
+Iterate it for events, oldest first, or format it with `{}` for a dump.
+*/
+#[derive(Debug, Clone)]
+pub struct ContentionLog<const N: usize> {
+    events: [Option<ContentionEvent>; N],
+    len: usize,
+    dropped: usize,
+}
+
+impl<const N: usize> ContentionLog<N> {
+    /**
+    The recorded events, oldest first.
+    */
+    pub fn events(&self) -> impl Iterator<Item = ContentionEvent> + '_ {
+        self.events[..self.len].iter().flatten().copied()
+    }
+
+    /**
+    The number of events that couldn't be recorded, because another thread was recording
+    (or because `N` is 0).
+    */
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /**
+    The events in folded-stack format, for `flamegraph.pl`, inferno and other flamegraph tools.
+
+    Format it with `{}`.  Each line is one distinct holder and waiter pair, as a two-frame
+    stack with the holder's site at the root, followed by how many times it happened.
+    So the widest towers are the sites holding the lock when others wanted it.
+    */
+    pub fn folded(&self) -> FoldedContention<'_, N> {
+        FoldedContention { log: self }
+    }
+}
+
+/**
+A [ContentionLog] in folded-stack format, from [ContentionLog::folded].
+*/
+#[derive(Debug)]
+pub struct FoldedContention<'a, const N: usize> {
+    log: &'a ContentionLog<N>,
+}
+
+fn same_sites(a: &ContentionEvent, b: &ContentionEvent) -> bool {
+    a.waiter == b.waiter && a.holder == b.holder
+}
+
+impl<'a, const N: usize> core::fmt::Display for FoldedContention<'a, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        //N is small, so count duplicates by rescanning rather than allocating a map
+        for (i, event) in self.log.events().enumerate() {
+            if self.log.events().take(i).any(|earlier| same_sites(&earlier, &event)) {
+                continue;
+            }
+            let count = self.log.events().skip(i).filter(|later| same_sites(later, &event)).count();
+            match event.holder {
+                Some(holder) => write!(f, "held at {}", holder)?,
+                None => f.write_str("held at unknown")?,
+            }
+            writeln!(f, ";waited at {} {}", event.waiter, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> core::fmt::Display for ContentionLog<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for event in self.events() {
+            write!(f, "{}: {} waited", event.time, event.waiter)?;
+            match event.holder {
+                Some(holder) => writeln!(f, " for {}", holder)?,
+                None => writeln!(f)?,
+            }
+        }
+        if self.dropped != 0 {
+            writeln!(f, "({} events dropped)", self.dropped)?;
+        }
+        Ok(())
+    }
+}
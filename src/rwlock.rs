@@ -0,0 +1,525 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A reader-writer variant of [AtomicLock](crate::AtomicLock). */
+
+use core::cell::UnsafeCell;
+use core::fmt::{Debug, Display};
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/*
+state layout:
+* WRITER: held exclusively
+* UPGRADABLE: held by the (single) upgradable reader, only reachable through lock_api
+* the remaining bits count readers, in units of READER
+ */
+pub(crate) const WRITER: usize = 1 << 0;
+pub(crate) const UPGRADABLE: usize = 1 << 1;
+pub(crate) const READER: usize = 1 << 2;
+
+/**
+The state word behind [AtomicRwLock], without any data.
+
+With the `lock_api` feature, this implements `lock_api::RawRwLock` and `lock_api::RawRwLockUpgrade`,
+so it can be used with `lock_api::RwLock`.
+*/
+#[derive(Debug, Default)]
+pub struct RawAtomicRwLock {
+    state: AtomicUsize,
+}
+
+impl RawAtomicRwLock {
+    /**
+    Creates a new, unlocked state.
+    */
+    pub const fn new() -> Self {
+        RawAtomicRwLock { state: AtomicUsize::new(0) }
+    }
+
+    pub(crate) fn try_read(&self) -> bool {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & WRITER != 0 {
+                return false;
+            }
+            //only leaked guards could get this far, and wrapping around would let a writer in with them
+            let new = state.checked_add(READER).expect("too many readers");
+            //other readers coming and going is not a reason to fail
+            match self.state.compare_exchange_weak(state, new, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return true,
+                Err(new) => state = new,
+            }
+        }
+    }
+
+    pub(crate) fn unlock_read(&self) {
+        let old = self.state.fetch_sub(READER, Ordering::Release);
+        debug_assert!(old >= READER);
+    }
+
+    pub(crate) fn try_write(&self) -> bool {
+        //test before test-and-set, as in AtomicLock; a held lock costs a shared read, not a write
+        self.state.load(Ordering::Relaxed) == 0
+            && self.state.compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed).is_ok()
+    }
+
+    pub(crate) fn unlock_write(&self) {
+        let old = self.state.fetch_and(!WRITER, Ordering::Release);
+        debug_assert!(old & WRITER != 0);
+    }
+
+    //the only reader becomes the writer; an upgradable reader counts as another reader
+    pub(crate) fn try_upgrade_read(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == READER
+            && self.state.compare_exchange(READER, WRITER, Ordering::Acquire, Ordering::Relaxed).is_ok()
+    }
+
+    pub(crate) fn downgrade(&self) {
+        //readers can't get in while we hold WRITER, so the state is exactly WRITER
+        self.state.store(READER, Ordering::Release);
+    }
+
+    #[cfg_attr(not(feature = "lock_api"), allow(dead_code))]
+    pub(crate) fn try_upgradable(&self) -> bool {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & (WRITER | UPGRADABLE) != 0 {
+                return false;
+            }
+            match self.state.compare_exchange_weak(state, state | UPGRADABLE, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return true,
+                Err(new) => state = new,
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "lock_api"), allow(dead_code))]
+    pub(crate) fn unlock_upgradable(&self) {
+        let old = self.state.fetch_and(!UPGRADABLE, Ordering::Release);
+        debug_assert!(old & UPGRADABLE != 0);
+    }
+
+    #[cfg_attr(not(feature = "lock_api"), allow(dead_code))]
+    pub(crate) fn try_upgrade(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == UPGRADABLE
+            && self.state.compare_exchange(UPGRADABLE, WRITER, Ordering::Acquire, Ordering::Relaxed).is_ok()
+    }
+
+    #[cfg_attr(not(feature = "lock_api"), allow(dead_code))]
+    pub(crate) fn upgradable_to_read(&self) {
+        let old = self.state.fetch_add(READER - UPGRADABLE, Ordering::Release);
+        debug_assert!(old & UPGRADABLE != 0);
+    }
+
+    #[cfg_attr(not(feature = "lock_api"), allow(dead_code))]
+    pub(crate) fn write_to_upgradable(&self) {
+        //as in downgrade, the state is exactly WRITER
+        self.state.store(UPGRADABLE, Ordering::Release);
+    }
+
+    pub(crate) fn load(&self) -> usize {
+        self.state.load(Ordering::Relaxed)
+    }
+}
+
+/**
+A reader-writer lock.
+
+Any number of readers, or a single writer, may hold the lock at once.  Like [AtomicLock](crate::AtomicLock),
+acquisition never waits: if the lock is unavailable you get None, and what you do about that is up to you.
+*/
+pub struct AtomicRwLock<T> {
+    raw: RawAtomicRwLock,
+    //only written when an attempt fails, so uncontended locking doesn't pay for them
+    failed_reads: AtomicUsize,
+    failed_writes: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+impl<T> AtomicRwLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        AtomicRwLock {
+            raw: RawAtomicRwLock::new(),
+            failed_reads: AtomicUsize::new(0),
+            failed_writes: AtomicUsize::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /**
+    Locks the lock for reading, if no writer holds it.
+
+    # Panics
+    If the reader count would overflow, which takes leaking read guards.
+    */
+    pub fn try_read(&self) -> Option<ReadGuard<'_, T>> {
+        if self.raw.try_read() {
+            Some(ReadGuard { lock: self })
+        } else {
+            self.failed_reads.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    /**
+    Locks the lock for writing, if nobody else holds it.
+    */
+    pub fn try_write(&self) -> Option<WriteGuard<'_, T>> {
+        if self.raw.try_write() {
+            Some(WriteGuard { lock: self })
+        } else {
+            self.failed_writes.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    /**
+    The number of readers holding the lock.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn reader_count(&self) -> usize {
+        self.raw.load() / READER
+    }
+
+    /**
+    Whether a writer holds the lock.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn is_write_locked(&self) -> bool {
+        self.raw.load() & WRITER != 0
+    }
+
+    /**
+    How often readers and writers have found the lock unavailable, for judging read/write skew.
+    */
+    pub fn stats(&self) -> RwLockStats {
+        RwLockStats {
+            failed_reads: self.failed_reads.load(Ordering::Relaxed),
+            failed_writes: self.failed_writes.load(Ordering::Relaxed),
+        }
+    }
+
+    /**
+    Zeroes the counters behind [AtomicRwLock::stats].
+    */
+    pub fn reset_stats(&self) {
+        self.failed_reads.store(0, Ordering::Relaxed);
+        self.failed_writes.store(0, Ordering::Relaxed);
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: Debug> Debug for AtomicRwLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        //not try_read, so formatting doesn't count as contention
+        let guard = if self.raw.try_read() { Some(ReadGuard { lock: self }) } else { None };
+        match guard {
+            None => {
+                f.debug_struct("AtomicRwLock")
+                    .field("write_locked", &true)
+                    .field("data", &format_args!("<locked>"))
+                    .finish()
+            }
+            Some(guard) => {
+                f.debug_struct("AtomicRwLock")
+                    .field("write_locked", &false)
+                    .field("data", &*guard)
+                    .finish()
+            }
+        }
+    }
+}
+
+impl<T> Default for AtomicRwLock<T> where T: Default {
+    fn default() -> Self {
+        AtomicRwLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for AtomicRwLock<T> {
+    fn from(data: T) -> Self {
+        AtomicRwLock::new(data)
+    }
+}
+
+/**
+Contention counters of an [AtomicRwLock], from [AtomicRwLock::stats].
+
+Each counter wraps on overflow.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RwLockStats {
+    failed_reads: usize,
+    failed_writes: usize,
+}
+
+impl RwLockStats {
+    /**
+    How many times [AtomicRwLock::try_read] found a writer holding the lock.
+    */
+    pub fn failed_reads(&self) -> usize {
+        self.failed_reads
+    }
+
+    /**
+    How many times [AtomicRwLock::try_write] found the lock held.
+    */
+    pub fn failed_writes(&self) -> usize {
+        self.failed_writes
+    }
+}
+
+//readers share &T across threads, so unlike AtomicLock we need T: Sync too
+unsafe impl<T: Send> Send for AtomicRwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for AtomicRwLock<T> {}
+
+/**
+A shared guard for [AtomicRwLock].
+
+Unlocks when dropped.
+*/
+#[must_use]
+pub struct ReadGuard<'a, T> {
+    lock: &'a AtomicRwLock<T>,
+}
+
+impl<'a, T> ReadGuard<'a, T> {
+    /**
+    Makes a new [MappedReadGuard] for a component of the locked data, keeping the read lock held.
+
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    Use it as `ReadGuard::map(guard, |data| &data.field)`.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn map<U: ?Sized, F>(guard: Self, f: F) -> MappedReadGuard<'a, T, U> where F: FnOnce(&T) -> &U {
+        let data: *const U = f(&guard);
+        MappedReadGuard { lock: ReadGuard::into_lock(guard), data, _marker: PhantomData }
+    }
+
+    /**
+    Like [ReadGuard::map], but `f` may decline by returning None, and you get the original guard back.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn try_map<U: ?Sized, F>(guard: Self, f: F) -> Result<MappedReadGuard<'a, T, U>, Self> where F: FnOnce(&T) -> Option<&U> {
+        match f(&guard) {
+            Some(data) => {
+                let data: *const U = data;
+                Ok(MappedReadGuard { lock: ReadGuard::into_lock(guard), data, _marker: PhantomData })
+            }
+            None => Err(guard),
+        }
+    }
+
+    /**
+    Turns the read lock into a write lock, if this is the only reader.  Otherwise, gives the guard back.
+
+    Suits check-then-modify code that usually only reads: check under the read lock, and upgrade
+    to modify.  The lock is never released in between, so what you checked still holds.
+    */
+    pub fn try_upgrade(guard: Self) -> Result<WriteGuard<'a, T>, Self> {
+        if guard.lock.raw.try_upgrade_read() {
+            Ok(WriteGuard { lock: ReadGuard::into_lock(guard) })
+        } else {
+            Err(guard)
+        }
+    }
+
+    //gives up the guard without unlocking, for guards that take over the lock
+    fn into_lock(guard: Self) -> &'a AtomicRwLock<T> {
+        let lock = guard.lock;
+        core::mem::forget(guard);
+        lock
+    }
+}
+
+impl<'a, T> Drop for ReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.raw.unlock_read();
+    }
+}
+
+impl<'a, T> core::ops::Deref for ReadGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> AsRef<T> for ReadGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> Debug for ReadGuard<'a, T> where T: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReadGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> Display for ReadGuard<'a, T> where T: Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/**
+A shared guard for some part of the data inside an [AtomicRwLock].
+
+Created by [ReadGuard::map] or [ReadGuard::try_map].  Unlocks the original lock when dropped.
+*/
+#[must_use]
+pub struct MappedReadGuard<'a, T, U: ?Sized> {
+    lock: &'a AtomicRwLock<T>,
+    data: *const U,
+    _marker: PhantomData<&'a U>,
+}
+
+impl<'a, T, U: ?Sized> MappedReadGuard<'a, T, U> {
+    /**
+    Projects the guard further, onto a component of the already-mapped data.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn map<V: ?Sized, F>(guard: Self, f: F) -> MappedReadGuard<'a, T, V> where F: FnOnce(&U) -> &V {
+        let data: *const V = f(&guard);
+        let lock = guard.lock;
+        core::mem::forget(guard);
+        MappedReadGuard { lock, data, _marker: PhantomData }
+    }
+
+    /**
+    Like [MappedReadGuard::map], but `f` may decline by returning None, and you get the original guard back.
+
+    If `f` panics, the lock is unlocked.
+    */
+    pub fn try_map<V: ?Sized, F>(guard: Self, f: F) -> Result<MappedReadGuard<'a, T, V>, Self> where F: FnOnce(&U) -> Option<&V> {
+        match f(&guard) {
+            Some(data) => {
+                let data: *const V = data;
+                let lock = guard.lock;
+                core::mem::forget(guard);
+                Ok(MappedReadGuard { lock, data, _marker: PhantomData })
+            }
+            None => Err(guard),
+        }
+    }
+}
+
+impl<'a, T, U: ?Sized> Drop for MappedReadGuard<'a, T, U> {
+    fn drop(&mut self) {
+        self.lock.raw.unlock_read();
+    }
+}
+
+impl<'a, T, U: ?Sized> core::ops::Deref for MappedReadGuard<'a, T, U> {
+    type Target = U;
+    fn deref(&self) -> &U {
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, T, U: ?Sized> AsRef<U> for MappedReadGuard<'a, T, U> {
+    fn as_ref(&self) -> &U {
+        self
+    }
+}
+
+impl<'a, T, U: ?Sized> Debug for MappedReadGuard<'a, T, U> where U: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MappedReadGuard")
+            .field("data", &&**self)
+            .finish()
+    }
+}
+
+impl<'a, T, U: ?Sized> Display for MappedReadGuard<'a, T, U> where U: Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+//we only hold a *const U for projection purposes, it behaves like &U
+unsafe impl<'a, T, U: ?Sized> Send for MappedReadGuard<'a, T, U> where U: Sync {}
+unsafe impl<'a, T, U: ?Sized> Sync for MappedReadGuard<'a, T, U> where U: Sync {}
+
+/**
+An exclusive guard for [AtomicRwLock].
+
+Unlocks when dropped.
+*/
+#[must_use]
+pub struct WriteGuard<'a, T> {
+    lock: &'a AtomicRwLock<T>,
+}
+
+impl<'a, T> WriteGuard<'a, T> {
+    /**
+    Atomically turns the write lock into a read lock, without letting another writer in.
+    */
+    pub fn downgrade(guard: Self) -> ReadGuard<'a, T> {
+        let lock = guard.lock;
+        core::mem::forget(guard);
+        lock.raw.downgrade();
+        ReadGuard { lock }
+    }
+}
+
+impl<'a, T> Drop for WriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.raw.unlock_write();
+    }
+}
+
+impl<'a, T> core::ops::Deref for WriteGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for WriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T> AsRef<T> for WriteGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> AsMut<T> for WriteGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T> Debug for WriteGuard<'a, T> where T: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WriteGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> Display for WriteGuard<'a, T> where T: Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
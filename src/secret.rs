@@ -0,0 +1,79 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A lock for secrets, which zeroizes its payload. */
+
+use core::mem::ManuallyDrop;
+use zeroize::{Zeroize, Zeroizing};
+use crate::{AtomicLock, Guard};
+
+/**
+An [AtomicLock] for key material and other secrets.
+
+The payload is zeroized when the lock is dropped.  Values moved out with [SecretAtomicLock::take] or
+[SecretAtomicLock::into_inner] come wrapped in [Zeroizing], so they are zeroized when you drop them, too.
+*/
+#[derive(Default)]
+pub struct SecretAtomicLock<T: Zeroize> {
+    lock: AtomicLock<T>,
+}
+
+impl<T: Zeroize> SecretAtomicLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        SecretAtomicLock { lock: AtomicLock::new(data) }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<Guard<'_, T>> {
+        self.lock.lock()
+    }
+
+    /**
+    Moves the secret out, leaving the default value behind.
+    If the lock is unavailable, will return None.
+    */
+    pub fn take(&self) -> Option<Zeroizing<T>> where T: Default {
+        self.lock().map(|mut guard| Zeroizing::new(core::mem::take(&mut *guard)))
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+
+    The bytes the data occupied inside the lock are zeroed.
+    */
+    pub fn into_inner(self) -> Zeroizing<T> {
+        let mut this = ManuallyDrop::new(self);
+        let data = this.lock.data.get_mut();
+        //Safety: `this` is never dropped or used again, so we own the only copy...
+        let value = unsafe { core::ptr::read(data) };
+        //...apart from the bytes left behind, which we now clear without dropping them.
+        unsafe { zeroize::zeroize_flat_type(data as *mut T) };
+        Zeroizing::new(value)
+    }
+}
+
+impl<T: Zeroize> Drop for SecretAtomicLock<T> {
+    fn drop(&mut self) {
+        self.lock.data.get_mut().zeroize();
+    }
+}
+
+//never print the secret
+impl<T: Zeroize> core::fmt::Debug for SecretAtomicLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SecretAtomicLock")
+            .field("locked", &self.lock.is_locked())
+            .field("data", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+impl<T: Zeroize> From<T> for SecretAtomicLock<T> {
+    fn from(data: T) -> Self {
+        SecretAtomicLock::new(data)
+    }
+}
@@ -0,0 +1,23 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Serializing locked data through its guard. */
+
+use serde::{Serialize, Serializer};
+use crate::{Guard, MappedGuard, StateWord};
+
+/**
+Serializes the locked data, so code that holds the lock can snapshot it without cloning it first.
+*/
+impl<'a, T: Serialize, S: StateWord> Serialize for Guard<'a, T, S> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+/**
+Serializes the mapped part of the locked data.
+*/
+impl<'a, T, U: Serialize, S: StateWord> Serialize for MappedGuard<'a, T, U, S> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        (**self).serialize(serializer)
+    }
+}
@@ -0,0 +1,216 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A lock for memory shared between processes, with recovery from dead holders. */
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/**
+A lock meant to live in memory shared between processes.
+
+# Layout
+
+This type is `#[repr(C)]`: an 8-byte state word at offset 0, followed by `T`.  The state word is
+`generation << 32 | owner`, where `owner` is the holder's process id, or 0 when unlocked.
+Every process mapping the lock must agree on `T`'s layout, so make `T` `#[repr(C)]` and free of pointers.
+
+# Placing it
+
+Initialize the memory once, e.g. with `core::ptr::write(ptr, SharedMemoryLock::new(data))`, then
+get a reference in each process with [SharedMemoryLock::from_ptr].
+
+# Recovery
+
+If a process dies while holding the lock, [SharedMemoryLock::try_recover] takes the lock over from it.
+Each acquisition bumps the generation, so the dead holder's guard (if the process somehow still has it)
+can no longer unlock, and any inconsistency in the data is flagged with [SharedGuard::is_recovered].
+*/
+#[repr(C)]
+pub struct SharedMemoryLock<T> {
+    state: AtomicU64,
+    data: UnsafeCell<T>,
+}
+
+const OWNER_MASK: u64 = 0xffff_ffff;
+
+const fn pack(generation: u32, owner: u32) -> u64 {
+    (generation as u64) << 32 | owner as u64
+}
+
+const fn owner(state: u64) -> u32 {
+    (state & OWNER_MASK) as u32
+}
+
+const fn generation(state: u64) -> u32 {
+    (state >> 32) as u32
+}
+
+impl<T> SharedMemoryLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        SharedMemoryLock {
+            state: AtomicU64::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /**
+    Gets a reference to a lock that was already initialized in shared memory.
+
+    # Safety
+    `ptr` must point to an initialized `SharedMemoryLock<T>` that stays mapped for `'a`,
+    with the same `T` in every process.
+    */
+    pub unsafe fn from_ptr<'a>(ptr: *const Self) -> &'a Self {
+        &*ptr
+    }
+
+    /**
+    Locks the lock on behalf of process `pid`, if available.
+    If the lock is unavailable, will return None.
+
+    # Panics
+    Panics if `pid` is 0, which means "unlocked".
+    */
+    pub fn lock(&self, pid: u32) -> Option<SharedGuard<'_, T>> {
+        assert!(pid != 0, "pid 0 is reserved for unlocked");
+        let state = self.state.load(Ordering::Relaxed);
+        if owner(state) != 0 {
+            return None;
+        }
+        self.acquire(state, pid, false)
+    }
+
+    /**
+    Takes the lock over from a holder that has died.
+
+    `is_alive` is asked whether the current holder's pid is still running; on unix, `kill(pid, 0)` is the
+    usual check.  If it isn't, the lock is given to `pid` with [SharedGuard::is_recovered] set.
+
+    Returns None if the lock is free (use [SharedMemoryLock::lock]), the holder is alive, or someone
+    else recovered it first.
+
+    # Safety
+    The recorded holder must really be dead, whatever `is_alive` says.  A holder that is still
+    running keeps its guard, and would access the data alongside the new one.
+
+    # Panics
+    Panics if `pid` is 0, which means "unlocked".
+    */
+    pub unsafe fn try_recover(&self, pid: u32, is_alive: impl FnOnce(u32) -> bool) -> Option<SharedGuard<'_, T>> {
+        assert!(pid != 0, "pid 0 is reserved for unlocked");
+        let state = self.state.load(Ordering::Relaxed);
+        let holder = owner(state);
+        if holder == 0 || is_alive(holder) {
+            return None;
+        }
+        self.acquire(state, pid, true)
+    }
+
+    /**
+    The pid of the current holder, or None if unlocked.
+
+    The answer may be stale by the time you read it.
+    */
+    pub fn owner(&self) -> Option<u32> {
+        match owner(self.state.load(Ordering::Relaxed)) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /**
+    The number of times the lock has been acquired, wrapping on overflow.
+    */
+    pub fn generation(&self) -> u32 {
+        generation(self.state.load(Ordering::Relaxed))
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    fn acquire(&self, state: u64, pid: u32, recovered: bool) -> Option<SharedGuard<'_, T>> {
+        let locked = pack(generation(state).wrapping_add(1), pid);
+        match self.state.compare_exchange(state, locked, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => Some(SharedGuard { lock: self, state: locked, recovered }),
+            Err(_) => None,
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for SharedMemoryLock<T> {}
+unsafe impl<T: Send> Sync for SharedMemoryLock<T> {}
+
+impl<T> core::fmt::Debug for SharedMemoryLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = self.state.load(Ordering::Relaxed);
+        f.debug_struct("SharedMemoryLock")
+            .field("owner", &owner(state))
+            .field("generation", &generation(state))
+            .finish()
+    }
+}
+
+/**
+A guard for [SharedMemoryLock].
+
+Unlocks when dropped, unless the lock was recovered by someone else in the meantime.
+*/
+#[must_use]
+pub struct SharedGuard<'a, T> {
+    lock: &'a SharedMemoryLock<T>,
+    //the state we installed, so we can tell if we were recovered from
+    state: u64,
+    recovered: bool,
+}
+
+impl<'a, T> SharedGuard<'a, T> {
+    /**
+    Whether we got this lock by recovering it from a dead holder, in which case the data may be
+    half-updated.
+    */
+    pub fn is_recovered(&self) -> bool {
+        self.recovered
+    }
+
+    /**
+    The generation of this acquisition.
+    */
+    pub fn generation(&self) -> u32 {
+        generation(self.state)
+    }
+}
+
+impl<'a, T> Drop for SharedGuard<'a, T> {
+    fn drop(&mut self) {
+        //keep the generation, clear the owner; if this fails, someone recovered the lock from us
+        let unlocked = self.state & !OWNER_MASK;
+        let _ = self.lock.state.compare_exchange(self.state, unlocked, Ordering::Release, Ordering::Relaxed);
+    }
+}
+
+impl<'a, T> core::ops::Deref for SharedGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for SharedGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T> core::fmt::Debug for SharedGuard<'a, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SharedGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
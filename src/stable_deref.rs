@@ -0,0 +1,16 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Guards whose data stays put when the guard moves. */
+
+use stable_deref_trait::StableDeref;
+use crate::{Guard, StateWord};
+
+//the data lives in the lock, which the guard borrows
+unsafe impl<'a, T, S: StateWord> StableDeref for Guard<'a, T, S> {}
+
+//the lock lives in the Rc's allocation, which the guard keeps alive
+#[cfg(feature = "alloc")]
+unsafe impl<T, S: StateWord> StableDeref for crate::RcGuard<T, S> {}
+
+//likewise for the Arc
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+unsafe impl<T, S: StateWord> StableDeref for crate::OwnedGuard<T, S> {}
@@ -0,0 +1,202 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! The lock state word.
+
+By default this is an `AtomicU8` wherever we have atomic compare-and-swap.  Elsewhere it is a
+`Cell<u8>` with the same methods, and [AtomicLock](crate::AtomicLock) is not `Sync`.
+[LocalAtomicLock](crate::LocalAtomicLock) uses the `Cell<u8>` everywhere.
+
+Other widths can be chosen through [StateWord].
+*/
+
+use core::sync::atomic::Ordering;
+#[cfg(target_has_atomic = "8")]
+use core::sync::atomic::AtomicBool;
+
+#[cfg(target_has_atomic = "8")]
+pub(crate) type State = core::sync::atomic::AtomicU8;
+
+#[cfg(not(target_has_atomic = "8"))]
+pub(crate) type State = single_threaded::CellState;
+
+pub use single_threaded::CellState;
+
+/**
+A type that can be the state word of an [AtomicLock](crate::AtomicLock), as in `AtomicLock<T, S>`.
+
+The default is `AtomicU8`, whose spare bits are reserved for future flags.  The wider atomic integers
+leave more room, or match a lock word whose layout is mandated elsewhere, like in a C struct;
+`AtomicBool` has no spare bits at all.  With any integer, the lowest bit is the locked flag, and
+the other bits are preserved.
+
+This trait is sealed: the implementations are the standard atomics, as available on the target.
+*/
+pub trait StateWord: sealed::Word {}
+
+pub(crate) mod sealed {
+    /**
+    The operations on a state word.  Public but unnameable, to seal [StateWord](super::StateWord).
+    */
+    pub trait Word {
+        //strong: never reports a free lock as busy
+        fn try_acquire(&self) -> bool;
+        fn release(&self);
+        //clears LOCKED whether or not it was set, keeping the other bits
+        fn force_release(&self);
+        fn is_held(&self) -> bool;
+        //the whole word, for reports
+        fn bits(&self) -> u64;
+    }
+}
+
+//for types with AtomicU8's methods
+macro_rules! integer_word {
+    ($word:ty, $int:ty) => {
+        impl sealed::Word for $word {
+            #[inline]
+            fn try_acquire(&self) -> bool {
+                const LOCKED: $int = crate::LOCKED as $int;
+                //a relaxed load first, so a busy lock costs a shared read rather than a write
+                let state = self.load(Ordering::Relaxed);
+                if state & LOCKED != 0 {
+                    return false;
+                }
+                self.compare_exchange(state, state | LOCKED, Ordering::Acquire, Ordering::Relaxed).is_ok()
+            }
+
+            #[inline]
+            fn release(&self) {
+                const LOCKED: $int = crate::LOCKED as $int;
+                #[cfg(any(debug_assertions, feature = "strict"))]
+                {
+                    let old = self.fetch_and(!LOCKED, Ordering::Release);
+                    assert!(old & LOCKED != 0, "unlocked an AtomicLock that was not locked");
+                }
+                /*
+                An RMW rather than a load and a store: a word shared with other code, as through
+                AtomicLockRef, may have its other bits written while it is LOCKED.
+                 */
+                #[cfg(not(any(debug_assertions, feature = "strict")))]
+                self.fetch_and(!LOCKED, Ordering::Release);
+            }
+
+            fn force_release(&self) {
+                const LOCKED: $int = crate::LOCKED as $int;
+                self.fetch_and(!LOCKED, Ordering::Release);
+            }
+
+            #[inline]
+            fn is_held(&self) -> bool {
+                const LOCKED: $int = crate::LOCKED as $int;
+                self.load(Ordering::Relaxed) & LOCKED != 0
+            }
+
+            fn bits(&self) -> u64 {
+                self.load(Ordering::Relaxed) as u64
+            }
+        }
+
+        impl StateWord for $word {}
+    };
+}
+
+#[cfg(target_has_atomic = "8")]
+integer_word!(core::sync::atomic::AtomicU8, u8);
+#[cfg(target_has_atomic = "16")]
+integer_word!(core::sync::atomic::AtomicU16, u16);
+#[cfg(target_has_atomic = "32")]
+integer_word!(core::sync::atomic::AtomicU32, u32);
+#[cfg(target_has_atomic = "64")]
+integer_word!(core::sync::atomic::AtomicU64, u64);
+#[cfg(target_has_atomic = "ptr")]
+integer_word!(core::sync::atomic::AtomicUsize, usize);
+integer_word!(single_threaded::CellState, u8);
+
+#[cfg(target_has_atomic = "8")]
+impl sealed::Word for AtomicBool {
+    #[inline]
+    fn try_acquire(&self) -> bool {
+        !self.load(Ordering::Relaxed) && self.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok()
+    }
+
+    #[inline]
+    fn release(&self) {
+        #[cfg(any(debug_assertions, feature = "strict"))]
+        {
+            let old = self.swap(false, Ordering::Release);
+            assert!(old, "unlocked an AtomicLock that was not locked");
+        }
+        #[cfg(not(any(debug_assertions, feature = "strict")))]
+        self.store(false, Ordering::Release);
+    }
+
+    fn force_release(&self) {
+        self.store(false, Ordering::Release);
+    }
+
+    #[inline]
+    fn is_held(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+
+    fn bits(&self) -> u64 {
+        self.load(Ordering::Relaxed) as u64
+    }
+}
+
+#[cfg(target_has_atomic = "8")]
+impl StateWord for AtomicBool {}
+
+mod single_threaded {
+    use core::cell::Cell;
+    use core::sync::atomic::Ordering;
+
+    /**
+    The default [StateWord](super::StateWord) on targets without atomic compare-and-swap,
+    and the state of [LocalAtomicLock](crate::LocalAtomicLock).
+
+    A `Cell<u8>`, so only correct on one thread; locks using it are not `Sync`.
+    */
+    //orderings are meaningless on one thread, but we take them so call sites match AtomicU8
+    pub struct CellState(Cell<u8>);
+
+    impl CellState {
+        /**
+        Creates a new state word.
+        */
+        pub const fn new(value: u8) -> Self {
+            CellState(Cell::new(value))
+        }
+
+        pub(crate) fn load(&self, _: Ordering) -> u8 {
+            self.0.get()
+        }
+
+        #[allow(dead_code)]
+        pub(crate) fn store(&self, value: u8, _: Ordering) {
+            self.0.set(value)
+        }
+
+        pub(crate) fn compare_exchange(&self, current: u8, new: u8, _: Ordering, _: Ordering) -> Result<u8, u8> {
+            let old = self.0.get();
+            if old == current {
+                self.0.set(new);
+                Ok(old)
+            } else {
+                Err(old)
+            }
+        }
+
+        #[allow(dead_code)]
+        pub(crate) fn fetch_and(&self, value: u8, _: Ordering) -> u8 {
+            let old = self.0.get();
+            self.0.set(old & value);
+            old
+        }
+    }
+
+    impl core::fmt::Debug for CellState {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.debug_tuple("CellState").field(&self.0.get()).finish()
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Lock statistics per thread, to attribute contention to the threads causing it.
+
+With the `thread-stats` feature, each thread counts the locks it takes, the locks it fails to take,
+and how long it holds them, in counters of its own.  [thread_lock_stats] collects them on demand.
+
+This covers every [AtomicLock](crate::AtomicLock), including those inside other types in this crate,
+at the same points the `profiling` feature reports.
+*/
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use std::thread::{self, ThreadId};
+use std::time::Instant;
+use crate::AtomicLock;
+
+//one thread's counters; the thread writes them, anyone may read them
+#[derive(Debug)]
+struct Counters {
+    thread: ThreadId,
+    name: Option<std::string::String>,
+    acquisitions: AtomicU64,
+    failures: AtomicU64,
+    hold_nanos: AtomicU64,
+}
+
+//every thread that has touched a lock, for collecting
+static THREADS: AtomicLock<Vec<Arc<Counters>>> = AtomicLock::new(Vec::new());
+
+std::thread_local! {
+    static COUNTERS: Arc<Counters> = register();
+    //locks this thread holds, and when it took them
+    static HELD: RefCell<Vec<(*const (), Instant)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn threads() -> crate::Guard<'static, Vec<Arc<Counters>>> {
+    //acquire doesn't report the hold, so registering from hold_begin doesn't count itself
+    loop {
+        if let Some(guard) = THREADS.acquire() {
+            return guard;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+fn register() -> Arc<Counters> {
+    let current = thread::current();
+    let counters = Arc::new(Counters {
+        thread: current.id(),
+        name: current.name().map(Into::into),
+        acquisitions: AtomicU64::new(0),
+        failures: AtomicU64::new(0),
+        hold_nanos: AtomicU64::new(0),
+    });
+    threads().push(counters.clone());
+    counters
+}
+
+//a thread that's exiting has lost its thread-locals; if so we just don't count
+fn with_counters(f: impl FnOnce(&Counters)) {
+    let _ = COUNTERS.try_with(|counters| f(counters));
+}
+
+pub(crate) fn failed() {
+    with_counters(|counters| {
+        counters.failures.fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+pub(crate) fn hold_begin(lock: *const ()) {
+    with_counters(|counters| {
+        counters.acquisitions.fetch_add(1, Ordering::Relaxed);
+    });
+    let _ = HELD.try_with(|held| held.borrow_mut().push((lock, Instant::now())));
+}
+
+pub(crate) fn hold_end(lock: *const ()) {
+    //a guard sent here from another thread isn't in our list, and its hold time is lost
+    let began = HELD.try_with(|held| {
+        let mut held = held.borrow_mut();
+        let position = held.iter().rposition(|(held, _)| *held == lock)?;
+        Some(held.remove(position).1)
+    });
+    if let Ok(Some(began)) = began {
+        let nanos = began.elapsed().as_nanos() as u64;
+        with_counters(|counters| {
+            counters.hold_nanos.fetch_add(nanos, Ordering::Relaxed);
+        });
+    }
+}
+
+/**
+One thread's lock statistics, from [thread_lock_stats].
+
+The counters are read one at a time while the thread runs, so they may not be exactly consistent with each other.
+*/
+#[derive(Debug, Clone)]
+pub struct ThreadLockStats {
+    thread: ThreadId,
+    name: Option<std::string::String>,
+    acquisitions: u64,
+    failures: u64,
+    hold_time: Duration,
+    running: bool,
+}
+
+impl ThreadLockStats {
+    fn new(counters: &Arc<Counters>) -> Self {
+        ThreadLockStats {
+            thread: counters.thread,
+            name: counters.name.clone(),
+            acquisitions: counters.acquisitions.load(Ordering::Relaxed),
+            failures: counters.failures.load(Ordering::Relaxed),
+            hold_time: Duration::from_nanos(counters.hold_nanos.load(Ordering::Relaxed)),
+            //the thread-local holds the other reference, until the thread exits
+            running: Arc::strong_count(counters) > 1,
+        }
+    }
+
+    /**
+    The thread these are for.
+    */
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread
+    }
+
+    /**
+    The thread's name, if it has one.
+    */
+    pub fn thread_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /**
+    How many locks the thread has taken.
+    */
+    pub fn acquisitions(&self) -> u64 {
+        self.acquisitions
+    }
+
+    /**
+    How many times [AtomicLock::lock](crate::AtomicLock::lock) found the lock busy on this thread.
+
+    Waiting functions in this crate count once per wait, not once per retry.
+    */
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /**
+    How long the thread has held locks in total, counting the ones it released itself.
+    */
+    pub fn hold_time(&self) -> Duration {
+        self.hold_time
+    }
+
+    /**
+    Whether the thread was still running.  An exited thread's stats are kept until [reset_thread_lock_stats].
+    */
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+/**
+Collects the statistics of every thread that has used a lock.
+*/
+pub fn thread_lock_stats() -> Vec<ThreadLockStats> {
+    threads().iter().map(ThreadLockStats::new).collect()
+}
+
+/**
+Collects the current thread's statistics.
+*/
+pub fn current_thread_lock_stats() -> ThreadLockStats {
+    COUNTERS.with(ThreadLockStats::new)
+}
+
+/**
+Zeroes every thread's counters, and forgets threads that have exited.
+*/
+pub fn reset_thread_lock_stats() {
+    let mut threads = threads();
+    threads.retain(|counters| Arc::strong_count(counters) > 1);
+    for counters in threads.iter() {
+        counters.acquisitions.store(0, Ordering::Relaxed);
+        counters.failures.store(0, Ordering::Relaxed);
+        counters.hold_nanos.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,92 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Guards that edit a copy, and only write it back on commit. */
+
+use core::fmt::{Debug, Display};
+use crate::{AtomicLock, Guard, StateWord};
+
+impl<T: Clone, S: StateWord> AtomicLock<T, S> {
+    /**
+    Locks the lock and starts a transaction on a clone of the data, if available.
+    If the lock is unavailable, will return None.
+
+    Changes go to the clone, and only replace the data on [TransactionalGuard::commit].
+    Dropping the guard, including by panicking, discards them.  The lock is held throughout,
+    so nobody sees half-finished edits either way.
+    */
+    pub fn lock_transaction(&self) -> Option<TransactionalGuard<'_, T, S>> {
+        self.lock().map(|guard| {
+            let copy = (*guard).clone();
+            TransactionalGuard { guard, copy }
+        })
+    }
+}
+
+/**
+A guard from [AtomicLock::lock_transaction], which derefs to a working copy of the data.
+
+Unlocks when dropped, discarding the working copy unless it was committed.
+*/
+#[must_use]
+pub struct TransactionalGuard<'a, T, S: StateWord = crate::State> {
+    guard: Guard<'a, T, S>,
+    copy: T,
+}
+
+impl<'a, T, S: StateWord> TransactionalGuard<'a, T, S> {
+    /**
+    Replaces the data with the working copy, and unlocks.
+
+    This is an associated function, so it doesn't conflict with methods on `T`.
+    */
+    pub fn commit(this: Self) {
+        let TransactionalGuard { mut guard, copy } = this;
+        *guard = copy;
+    }
+
+    /**
+    The data as it was when the transaction started, for validating or diffing against.
+    */
+    pub fn original(this: &Self) -> &T {
+        &this.guard
+    }
+}
+
+impl<'a, T, S: StateWord> core::ops::Deref for TransactionalGuard<'a, T, S> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.copy
+    }
+}
+
+impl<'a, T, S: StateWord> core::ops::DerefMut for TransactionalGuard<'a, T, S> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.copy
+    }
+}
+
+impl<'a, T, S: StateWord> AsRef<T> for TransactionalGuard<'a, T, S> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T, S: StateWord> AsMut<T> for TransactionalGuard<'a, T, S> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T: Debug, S: StateWord> Debug for TransactionalGuard<'a, T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TransactionalGuard")
+            .field("original", &*self.guard)
+            .field("data", &self.copy)
+            .finish()
+    }
+}
+
+impl<'a, T: Display, S: StateWord> Display for TransactionalGuard<'a, T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
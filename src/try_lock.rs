@@ -0,0 +1,235 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! The [TryLock] traits, for code generic over the crate's locks. */
+
+use core::ops::{Deref, DerefMut};
+use crate::{AtomicLock, Guard, StateWord};
+
+/**
+A lock that can be tried without waiting.
+
+Implemented by [AtomicLock] and the other exclusive locks in this crate, so generic code and
+adapters can be written once for all of them.  What to do when the lock is busy is still up to you.
+*/
+pub trait TryLock {
+    /**
+    The data the lock protects.
+    */
+    type Target: ?Sized;
+    /**
+    The guard returned by [TryLock::try_lock], which unlocks when dropped.
+    */
+    type Guard<'a>: DerefMut<Target = Self::Target> where Self: 'a;
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    fn try_lock(&self) -> Option<Self::Guard<'_>>;
+}
+
+/**
+A lock that can be tried for reading without waiting.
+
+Implemented by [AtomicRwLock](crate::AtomicRwLock), whose readers share the lock, and by
+[AtomicLock], for which reading is just locking.  Generic code that only reads can take either.
+*/
+pub trait TryLockRead {
+    /**
+    The data the lock protects.
+    */
+    type Target: ?Sized;
+    /**
+    The guard returned by [TryLockRead::try_read], which unlocks when dropped.
+    */
+    type ReadGuard<'a>: Deref<Target = Self::Target> where Self: 'a;
+    /**
+    Locks the lock for reading and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    fn try_read(&self) -> Option<Self::ReadGuard<'_>>;
+}
+
+/**
+A lock that can be tried for writing without waiting, as well as for reading.
+
+Implemented by [AtomicRwLock](crate::AtomicRwLock) and [AtomicLock].
+*/
+pub trait TryLockWrite: TryLockRead {
+    /**
+    The guard returned by [TryLockWrite::try_write], which unlocks when dropped.
+    */
+    type WriteGuard<'a>: DerefMut<Target = Self::Target> where Self: 'a;
+    /**
+    Locks the lock for writing and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    fn try_write(&self) -> Option<Self::WriteGuard<'_>>;
+}
+
+impl<T, S: StateWord> TryLockRead for AtomicLock<T, S> {
+    type Target = T;
+    type ReadGuard<'a> = Guard<'a, T, S> where Self: 'a;
+    #[inline]
+    fn try_read(&self) -> Option<Self::ReadGuard<'_>> {
+        self.lock()
+    }
+}
+
+impl<T, S: StateWord> TryLockWrite for AtomicLock<T, S> {
+    type WriteGuard<'a> = Guard<'a, T, S> where Self: 'a;
+    #[inline]
+    fn try_write(&self) -> Option<Self::WriteGuard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> TryLockRead for crate::AtomicRwLock<T> {
+    type Target = T;
+    type ReadGuard<'a> = crate::ReadGuard<'a, T> where Self: 'a;
+    fn try_read(&self) -> Option<Self::ReadGuard<'_>> {
+        crate::AtomicRwLock::try_read(self)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> TryLockWrite for crate::AtomicRwLock<T> {
+    type WriteGuard<'a> = crate::WriteGuard<'a, T> where Self: 'a;
+    fn try_write(&self) -> Option<Self::WriteGuard<'_>> {
+        crate::AtomicRwLock::try_write(self)
+    }
+}
+
+impl<T, S: StateWord> TryLock for AtomicLock<T, S> {
+    type Target = T;
+    type Guard<'a> = Guard<'a, T, S> where Self: 'a;
+    #[inline]
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(target_has_atomic = "8")]
+impl<T> TryLock for crate::BiasedLock<T> {
+    type Target = T;
+    type Guard<'a> = Guard<'a, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+impl<T, H: crate::PriorityHooks> TryLock for crate::CeilingLock<T, H> {
+    type Target = T;
+    type Guard<'a> = crate::CeilingGuard<'a, T, H> where Self: 'a;
+    #[track_caller]
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl<T> TryLock for crate::CombiningLock<T> {
+    type Target = T;
+    type Guard<'a> = crate::CombiningGuard<'a, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(all(feature = "elision", target_arch = "x86_64"))]
+impl<T> TryLock for crate::ElisionLock<T> {
+    type Target = T;
+    type Guard<'a> = crate::ElisionGuard<'a, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+impl<T, F: Fn()> TryLock for crate::HookedLock<T, F> {
+    type Target = T;
+    type Guard<'a> = crate::HookedGuard<'a, T, F> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(all(feature = "std", target_has_atomic = "ptr"))]
+impl<T, const MAX_SPINS: u32, const MAX_YIELDS: u32> TryLock for crate::HybridLock<T, MAX_SPINS, MAX_YIELDS> {
+    type Target = T;
+    type Guard<'a> = crate::HybridGuard<'a, T, MAX_SPINS, MAX_YIELDS> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        crate::HybridLock::try_lock(self)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<'p, T> TryLock for crate::IntentLock<'p, T> {
+    type Target = T;
+    type Guard<'a> = crate::IntentGuard<'a, 'p, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        crate::IntentLock::try_lock(self)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> TryLock for crate::InvariantLock<T> {
+    type Target = T;
+    type Guard<'a> = crate::InvariantGuard<'a, T> where Self: 'a;
+    #[track_caller]
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> TryLock for crate::LeakCheckedLock<T> {
+    type Target = T;
+    type Guard<'a> = Guard<'a, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+impl<T> TryLock for crate::LocalAtomicLock<T> {
+    type Target = T;
+    type Guard<'a> = Guard<'a, T, crate::CellState> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> TryLock for crate::PriorityLock<T> {
+    type Target = T;
+    type Guard<'a> = Guard<'a, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize> TryLock for crate::SecretAtomicLock<T> {
+    type Target = T;
+    type Guard<'a> = Guard<'a, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> TryLock for crate::WatchedLock<T> {
+    type Target = T;
+    type Guard<'a> = crate::WatchedGuard<'a, T> where Self: 'a;
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
+
+#[cfg(all(feature = "watchdog", target_has_atomic = "ptr"))]
+impl<T, C: crate::Clock> TryLock for crate::WatchdogLock<T, C> {
+    type Target = T;
+    type Guard<'a> = crate::WatchdogGuard<'a, T, C> where Self: 'a;
+    #[track_caller]
+    fn try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.lock()
+    }
+}
@@ -0,0 +1,104 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locks whose data is produced after the lock, e.g. at runtime for a static. */
+
+use core::mem::MaybeUninit;
+use core::sync::atomic::Ordering;
+use crate::state::State;
+use crate::{AtomicLock, Guard, MappedGuard, LOCKED, UNINIT};
+
+impl<T> AtomicLock<MaybeUninit<T>> {
+    /**
+    Creates a lock over uninitialized data.  It starts locked, so nobody can see the data
+    until someone initializes it.
+
+    This is for static locks whose data is only known at runtime: claim the data with
+    [AtomicLock::lock_uninit], then [UninitGuard::write] it.  After that, [AtomicLock::lock_init]
+    locks it as a `T`.
+
+    The data is not dropped with the lock; use [AtomicLock::into_init] for that.
+    */
+    pub const fn uninit() -> Self {
+        AtomicLock {
+            lock: State::new(LOCKED | UNINIT),
+            data: core::cell::UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /**
+    Claims the uninitialized data, to initialize it.
+
+    Returns None if the data has been initialized, or someone else is initializing it.
+    */
+    pub fn lock_uninit(&self) -> Option<UninitGuard<'_, T>> {
+        //LOCKED stays set, so nobody else gets in while we write
+        match self.lock.compare_exchange(LOCKED | UNINIT, LOCKED, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => Some(UninitGuard { lock: self }),
+            Err(_) => None,
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if it is available and has been initialized.
+    If the lock is unavailable, or the data isn't initialized, will return None.
+    */
+    pub fn lock_init(&self) -> Option<MappedGuard<'_, MaybeUninit<T>, T>> {
+        let guard = self.lock()?;
+        //stable while we hold the lock
+        if self.lock.load(Ordering::Relaxed) & UNINIT != 0 {
+            return None;
+        }
+        Some(Guard::map(guard, |data| unsafe { data.assume_init_mut() }))
+    }
+
+    /**
+    Consumes the lock, returning the data if it was initialized.
+    */
+    pub fn into_init(self) -> Option<T> {
+        let initialized = self.lock.load(Ordering::Relaxed) & UNINIT == 0 && !self.is_locked();
+        let data = self.into_inner();
+        if initialized {
+            Some(unsafe { data.assume_init() })
+        } else {
+            None
+        }
+    }
+}
+
+/**
+A claim on the uninitialized data of an [AtomicLock::uninit], to initialize it.
+
+If dropped without [UninitGuard::write], the data stays uninitialized and can be claimed again.
+*/
+#[must_use]
+pub struct UninitGuard<'a, T> {
+    lock: &'a AtomicLock<MaybeUninit<T>>,
+}
+
+impl<'a, T> UninitGuard<'a, T> {
+    /**
+    Initializes the data, returning a guard for it.  Other threads can lock it once the guard is dropped.
+    */
+    pub fn write(self, value: T) -> MappedGuard<'a, MaybeUninit<T>, T> {
+        let lock = self.lock;
+        core::mem::forget(self);
+        unsafe { (*lock.data.get()).write(value) };
+        //we've held LOCKED since lock_uninit, and cleared UNINIT then
+        let guard = Guard { lock, data: unsafe { &mut *lock.data.get() } };
+        //the hold starts here, as the guard will report its end
+        #[cfg(feature = "profiling")]
+        crate::profiling::hold_begin(lock as *const AtomicLock<MaybeUninit<T>> as *const ());
+        Guard::map(guard, |data| unsafe { data.assume_init_mut() })
+    }
+}
+
+impl<'a, T> Drop for UninitGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.lock.store(LOCKED | UNINIT, Ordering::Release);
+    }
+}
+
+impl<'a, T> core::fmt::Debug for UninitGuard<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UninitGuard").finish_non_exhaustive()
+    }
+}
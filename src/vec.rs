@@ -0,0 +1,143 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A growable collection of independently lockable slots. */
+
+use alloc::vec::Vec;
+use crate::AtomicLock;
+use crate::padded::CachePadded;
+
+/**
+A growable vector where each element is its own [AtomicLock].
+
+Each slot is padded to a cache line, so contention on one slot doesn't slow down its neighbours.
+
+Growing the vector requires `&mut self`; once it is shared, each slot can be locked independently.
+*/
+#[derive(Debug)]
+pub struct AtomicLockVec<T> {
+    slots: Vec<CachePadded<AtomicLock<T>>>,
+}
+
+impl<T> AtomicLockVec<T> {
+    /**
+    Creates a new, empty vector.
+    */
+    pub const fn new() -> Self {
+        AtomicLockVec { slots: Vec::new() }
+    }
+
+    /**
+    Creates a new, empty vector with room for `capacity` slots.
+    */
+    pub fn with_capacity(capacity: usize) -> Self {
+        AtomicLockVec { slots: Vec::with_capacity(capacity) }
+    }
+
+    /**
+    Appends a new, unlocked slot.
+    */
+    pub fn push(&mut self, data: T) {
+        self.slots.push(CachePadded(AtomicLock::new(data)));
+    }
+
+    /**
+    Returns the lock for slot `index`, or None if it is out of bounds.
+    */
+    pub fn get(&self, index: usize) -> Option<&AtomicLock<T>> {
+        self.slots.get(index).map(|slot| &**slot)
+    }
+
+    /**
+    The number of slots.
+    */
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /**
+    Whether there are no slots.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /**
+    Iterates over the lock for each slot.
+    */
+    pub fn iter(&self) -> impl Iterator<Item = &AtomicLock<T>> {
+        self.slots.iter().map(|slot| &**slot)
+    }
+
+    /**
+    Iterates over the data in each slot.
+
+    Since we have `&mut self`, no locking is required.
+    */
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().map(|slot| slot.0.data.get_mut())
+    }
+
+    /**
+    Consumes the vector, returning the inner data.
+    */
+    pub fn into_inner(self) -> Vec<T> {
+        self.slots.into_iter().map(|slot| slot.0.into_inner()).collect()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send> AtomicLockVec<T> {
+    /**
+    Iterates in parallel over the lock for each slot.
+    */
+    pub fn par_iter(&self) -> impl rayon::iter::IndexedParallelIterator<Item = &AtomicLock<T>> {
+        use rayon::prelude::*;
+        self.slots.par_iter().map(|slot| &**slot)
+    }
+
+    /**
+    Tries to lock each slot in parallel, yielding None for slots that are busy.
+
+    The items line up with the slots, so this can be zipped or enumerated.
+    To skip busy slots instead, `.flatten()` it.
+    */
+    pub fn par_lock(&self) -> impl rayon::iter::IndexedParallelIterator<Item = Option<crate::Guard<'_, T>>> {
+        use rayon::prelude::*;
+        self.slots.par_iter().map(|slot| slot.lock())
+    }
+
+    /**
+    Iterates in parallel over the data in each slot.
+
+    Since we have `&mut self`, no locking is required.
+    */
+    pub fn par_iter_mut(&mut self) -> impl rayon::iter::IndexedParallelIterator<Item = &mut T> {
+        use rayon::prelude::*;
+        self.slots.par_iter_mut().map(|slot| slot.0.data.get_mut())
+    }
+}
+
+//boilerplate
+
+impl<T> Default for AtomicLockVec<T> {
+    fn default() -> Self {
+        AtomicLockVec::new()
+    }
+}
+
+impl<T> From<Vec<T>> for AtomicLockVec<T> {
+    fn from(data: Vec<T>) -> Self {
+        data.into_iter().collect()
+    }
+}
+
+impl<T> FromIterator<T> for AtomicLockVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        AtomicLockVec { slots: iter.into_iter().map(|data| CachePadded(AtomicLock::new(data))).collect() }
+    }
+}
+
+impl<T> Extend<T> for AtomicLockVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.slots.extend(iter.into_iter().map(|data| CachePadded(AtomicLock::new(data))));
+    }
+}
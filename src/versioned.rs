@@ -0,0 +1,142 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locks whose data carries a version, for detecting stale caches. */
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+An [AtomicLock] with a version, which goes up each time the lock is unlocked after
+the data was accessed mutably.
+
+Caches built on top can remember the version they copied, and compare it with
+[VersionedLock::version] to tell whether they're stale, without comparing payloads or taking the lock.
+Unlike [WatchedLock](crate::WatchedLock), read-only locks don't count.
+*/
+#[derive(Debug, Default)]
+pub struct VersionedLock<T> {
+    lock: AtomicLock<T>,
+    version: AtomicUsize,
+}
+
+impl<T> VersionedLock<T> {
+    /**
+    Creates a new lock, at version 0.
+    */
+    pub const fn new(data: T) -> Self {
+        VersionedLock {
+            lock: AtomicLock::new(data),
+            version: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<VersionedGuard<'_, T>> {
+        self.lock.lock().map(|guard| VersionedGuard {
+            version: self.version.load(Ordering::Relaxed),
+            guard,
+            lock: self,
+            mutated: false,
+        })
+    }
+
+    /**
+    The current version, without taking the lock.  Wraps on overflow.
+
+    The answer may be stale by the time you read it, but once you see a version,
+    you also see the writes that made it.
+    */
+    pub fn version(&self) -> usize {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T> From<T> for VersionedLock<T> {
+    fn from(data: T) -> Self {
+        VersionedLock::new(data)
+    }
+}
+
+/**
+A guard for [VersionedLock].
+
+If the data was accessed mutably, bumps the version.  Unlocks when dropped.
+*/
+#[must_use]
+pub struct VersionedGuard<'a, T> {
+    guard: Guard<'a, T>,
+    lock: &'a VersionedLock<T>,
+    version: usize,
+    mutated: bool,
+}
+
+impl<'a, T> VersionedGuard<'a, T> {
+    /**
+    The version of the data when the guard was taken.
+
+    Nobody else can change it while the guard is alive; our own changes bump it on drop.
+    */
+    pub fn version(&self) -> usize {
+        self.version
+    }
+}
+
+impl<'a, T> Drop for VersionedGuard<'a, T> {
+    fn drop(&mut self) {
+        if self.mutated {
+            //before the guard field unlocks; release, so a reader who sees the version sees our writes
+            self.lock.version.store(self.version.wrapping_add(1), Ordering::Release);
+        }
+    }
+}
+
+impl<'a, T> core::ops::Deref for VersionedGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for VersionedGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        //we can't see whether anything was written, so any mutable access counts
+        self.mutated = true;
+        &mut self.guard
+    }
+}
+
+impl<'a, T> AsRef<T> for VersionedGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> AsMut<T> for VersionedGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T> core::fmt::Debug for VersionedGuard<'a, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VersionedGuard")
+            .field("version", &self.version)
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> core::fmt::Display for VersionedGuard<'a, T> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,79 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Waiting for a group of tasks to finish, without blocking. */
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/**
+A counter of outstanding work, for fan-out/fan-in.
+
+[TryWaitGroup::add] before handing out work, [TryWaitGroup::done] as each piece finishes,
+and poll [TryWaitGroup::try_wait] to see whether it's all done.  Nothing blocks;
+what you do while the work is outstanding is up to you.
+*/
+#[derive(Debug, Default)]
+pub struct TryWaitGroup {
+    outstanding: AtomicUsize,
+}
+
+impl TryWaitGroup {
+    /**
+    Creates a new group, with nothing outstanding.
+    */
+    pub const fn new() -> Self {
+        TryWaitGroup { outstanding: AtomicUsize::new(0) }
+    }
+
+    /**
+    Adds `n` pieces of outstanding work.
+
+    # Panics
+    If the count would overflow, in which case it's left as it was.
+    */
+    pub fn add(&self, n: usize) {
+        let mut old = self.outstanding.load(Ordering::Relaxed);
+        loop {
+            let new = old.checked_add(n).expect("TryWaitGroup overflowed");
+            match self.outstanding.compare_exchange_weak(old, new, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(actual) => old = actual,
+            }
+        }
+    }
+
+    /**
+    Marks one piece of work as done.  Returns true if it was the last one outstanding.
+
+    # Panics
+    If nothing was outstanding, in which case the count stays 0.
+    */
+    pub fn done(&self) -> bool {
+        let mut old = self.outstanding.load(Ordering::Relaxed);
+        loop {
+            let new = old.checked_sub(1).expect("TryWaitGroup::done called more times than add");
+            //release, so whoever sees the group done sees the work
+            match self.outstanding.compare_exchange_weak(old, new, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return old == 1,
+                Err(actual) => old = actual,
+            }
+        }
+    }
+
+    /**
+    Returns Ok if all the work is done, or else Err with the number of pieces outstanding.
+
+    Once this returns Ok, everything the workers did before [TryWaitGroup::done] is visible.
+    */
+    pub fn try_wait(&self) -> Result<(), usize> {
+        match self.outstanding.load(Ordering::Acquire) {
+            0 => Ok(()),
+            outstanding => Err(outstanding),
+        }
+    }
+
+    /**
+    Whether all the work is done.  The same as `try_wait().is_ok()`.
+    */
+    pub fn is_done(&self) -> bool {
+        self.try_wait().is_ok()
+    }
+}
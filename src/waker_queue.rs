@@ -0,0 +1,204 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Queues of tasks waiting on our async primitives.
+
+This is the async counterpart of the `parker` queue: a pending future registers its waker,
+then tries again; whoever makes that attempt succeed does it first, then wakes.
+A fence on each side means either the retry sees the change, or the waker sees the registration.
+*/
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::Waker;
+use crate::{AtomicLock, Guard};
+
+/**
+A queue of wakers, oldest first.
+*/
+#[derive(Debug)]
+pub(crate) struct WakerQueue {
+    wakers: AtomicLock<Vec<(usize, Waker)>>,
+    //mirrors wakers.len(), so waking can skip the queue when nobody waits
+    waiting: AtomicUsize,
+    next_id: AtomicUsize,
+}
+
+impl WakerQueue {
+    pub(crate) const fn new() -> Self {
+        WakerQueue {
+            wakers: AtomicLock::new(Vec::new()),
+            waiting: AtomicUsize::new(0),
+            next_id: AtomicUsize::new(0),
+        }
+    }
+
+    fn queue(&self) -> Guard<'_, Vec<(usize, Waker)>> {
+        loop {
+            if let Some(guard) = self.wakers.lock() {
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /**
+    Wakes the oldest waiting task, if any.  Call after making its attempt succeed.
+    */
+    pub(crate) fn wake_one(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let waker = {
+            let mut queue = self.queue();
+            let waker = if queue.is_empty() { None } else { Some(queue.remove(0).1) };
+            self.waiting.store(queue.len(), Ordering::Relaxed);
+            waker
+        };
+        //outside the queue lock, since waking may run arbitrary code
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /**
+    Wakes the oldest waiting task, or if nobody waits, calls `otherwise` before anyone else can register.
+
+    For when the wakeup has to be kept for later, like a permit.
+    */
+    pub(crate) fn wake_one_or(&self, otherwise: impl FnOnce()) {
+        let waker = {
+            let mut queue = self.queue();
+            if queue.is_empty() {
+                otherwise();
+                return;
+            }
+            let waker = queue.remove(0).1;
+            self.waiting.store(queue.len(), Ordering::Relaxed);
+            waker
+        };
+        waker.wake();
+    }
+
+    /**
+    Wakes every waiting task.  Call after making their attempts succeed.
+    */
+    pub(crate) fn wake_all(&self) {
+        core::sync::atomic::fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let mut wakers = {
+            let mut queue = self.queue();
+            self.waiting.store(0, Ordering::Relaxed);
+            core::mem::take(&mut *queue)
+        };
+        for (_, waker) in wakers.drain(..) {
+            waker.wake();
+        }
+        //give the allocation back, so a steady number of waiters doesn't allocate each time
+        let mut queue = self.queue();
+        if queue.capacity() < wakers.capacity() {
+            wakers.append(&mut queue);
+            *queue = wakers;
+        }
+    }
+}
+
+/**
+One future's place in a [WakerQueue].
+
+Dropping a registration that was woken, but never completed, passes the wakeup on to the next
+task, so a cancelled future can't swallow it.
+*/
+#[derive(Debug)]
+pub(crate) struct Registration<'a> {
+    queue: &'a WakerQueue,
+    id: Option<usize>,
+}
+
+impl<'a> Registration<'a> {
+    pub(crate) const fn new(queue: &'a WakerQueue) -> Self {
+        Registration { queue, id: None }
+    }
+
+    /**
+    Queues `waker`, or updates our entry if we're still queued.  Try again after this.
+    */
+    pub(crate) fn register(&mut self, waker: &Waker) {
+        self.enqueue(waker, false);
+    }
+
+    /**
+    Like [Registration::register], but if we were woken since we last registered, leaves
+    the queue and returns true instead.  For futures the wakeup itself is meant for.
+    */
+    pub(crate) fn register_or_take_wakeup(&mut self, waker: &Waker) -> bool {
+        self.enqueue(waker, true)
+    }
+
+    /**
+    Whether we were woken since we last registered.  Either way, we're out of the queue after.
+    */
+    pub(crate) fn take_wakeup(&mut self) -> bool {
+        self.id.is_some() && !self.leave()
+    }
+
+    fn enqueue(&mut self, waker: &Waker, take_wakeup: bool) -> bool {
+        {
+            let mut queue = self.queue.queue();
+            let entry = self.id.and_then(|id| queue.iter_mut().find(|(entry, _)| *entry == id));
+            match entry {
+                Some((_, queued)) => {
+                    if !queued.will_wake(waker) {
+                        *queued = waker.clone();
+                    }
+                }
+                None if take_wakeup && self.id.is_some() => {
+                    self.id = None;
+                    return true;
+                }
+                None => {
+                    let id = self.queue.next_id.fetch_add(1, Ordering::Relaxed);
+                    queue.push((id, waker.clone()));
+                    self.id = Some(id);
+                }
+            }
+            self.queue.waiting.store(queue.len(), Ordering::Relaxed);
+        }
+        //pairs with the fence in wake_one: either our retry sees their change, or they see our entry
+        core::sync::atomic::fence(Ordering::SeqCst);
+        false
+    }
+
+    /**
+    Leaves the queue, for a future that got what it was waiting for.
+    */
+    pub(crate) fn complete(&mut self) {
+        self.leave();
+    }
+
+    //whether we were still queued, i.e. not woken
+    fn leave(&mut self) -> bool {
+        let id = match self.id.take() {
+            Some(id) => id,
+            None => return true,
+        };
+        let mut queue = self.queue.queue();
+        match queue.iter().position(|(entry, _)| *entry == id) {
+            Some(position) => {
+                queue.remove(position);
+                self.queue.waiting.store(queue.len(), Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<'a> Drop for Registration<'a> {
+    fn drop(&mut self) {
+        if !self.leave() {
+            self.queue.wake_one();
+        }
+    }
+}
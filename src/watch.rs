@@ -0,0 +1,153 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Cheaply detecting that locked data may have changed. */
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crate::{AtomicLock, Guard};
+
+/**
+An [AtomicLock] that counts its unlocks, so pollers can tell when the data may have changed
+without taking the lock.
+
+Get a [LockWatcher] with [WatchedLock::watch].
+*/
+#[derive(Debug, Default)]
+pub struct WatchedLock<T> {
+    lock: AtomicLock<T>,
+    unlocks: AtomicUsize,
+}
+
+impl<T> WatchedLock<T> {
+    /**
+    Creates a new lock
+    */
+    pub const fn new(data: T) -> Self {
+        WatchedLock {
+            lock: AtomicLock::new(data),
+            unlocks: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    pub fn lock(&self) -> Option<WatchedGuard<'_, T>> {
+        self.lock.lock().map(|guard| WatchedGuard { guard, unlocks: &self.unlocks })
+    }
+
+    /**
+    Returns a handle that watches for unlocks.
+    */
+    pub fn watch(&self) -> LockWatcher<'_> {
+        LockWatcher { unlocks: &self.unlocks }
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T> From<T> for WatchedLock<T> {
+    fn from(data: T) -> Self {
+        WatchedLock::new(data)
+    }
+}
+
+/**
+A guard for [WatchedLock].
+
+Counts an unlock and unlocks when dropped.
+*/
+#[must_use]
+pub struct WatchedGuard<'a, T> {
+    guard: Guard<'a, T>,
+    unlocks: &'a AtomicUsize,
+}
+
+impl<'a, T> Drop for WatchedGuard<'a, T> {
+    fn drop(&mut self) {
+        //count before the guard field unlocks; release, so a watcher who sees the count sees our writes
+        self.unlocks.fetch_add(1, Ordering::Release);
+    }
+}
+
+impl<'a, T> core::ops::Deref for WatchedGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for WatchedGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T> AsRef<T> for WatchedGuard<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T> AsMut<T> for WatchedGuard<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T> core::fmt::Debug for WatchedGuard<'a, T> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WatchedGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
+
+impl<'a, T> core::fmt::Display for WatchedGuard<'a, T> where T: core::fmt::Display {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/**
+A handle that watches a [WatchedLock] for unlocks.
+
+Watching never takes the lock; it's a single atomic load.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct LockWatcher<'a> {
+    unlocks: &'a AtomicUsize,
+}
+
+impl<'a> LockWatcher<'a> {
+    /**
+    A token for the current state, to compare against later.
+    */
+    pub fn token(&self) -> WatchToken {
+        WatchToken(self.unlocks.load(Ordering::Acquire))
+    }
+
+    /**
+    Whether the lock has been unlocked (and so the data may have changed) since `token` was taken.
+    */
+    pub fn has_changed_since(&self, token: WatchToken) -> bool {
+        self.token() != token
+    }
+
+    /**
+    The number of times the lock has been unlocked.  Increases monotonically, wrapping on overflow.
+    */
+    pub fn unlock_count(&self) -> usize {
+        self.unlocks.load(Ordering::Acquire)
+    }
+}
+
+/**
+A point in a [WatchedLock]'s history, from [LockWatcher::token].
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WatchToken(usize);
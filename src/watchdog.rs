@@ -0,0 +1,212 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A watchdog for guards that are held too long. */
+
+use core::mem::ManuallyDrop;
+use core::panic::Location;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use crate::{AtomicLock, Clock, Guard};
+
+/**
+An [AtomicLock] that complains when a guard is held longer than a threshold.
+
+When a [WatchdogGuard] held for `threshold` ticks or more is dropped, the long-hold handler
+installed with [set_long_hold_handler] is called with a [LongHold], naming where the lock was taken.
+With no handler installed, it panics.
+
+The watchdog only runs in debug builds, or with the `strict` feature.  Otherwise this is a plain
+[AtomicLock], and doesn't read the clock.
+*/
+pub struct WatchdogLock<T, C> {
+    lock: AtomicLock<T>,
+    clock: C,
+    threshold: u64,
+}
+
+impl<T, C: Clock> WatchdogLock<T, C> {
+    /**
+    Creates a new lock, which complains about guards held for `threshold` ticks of `clock` or more.
+    */
+    pub const fn new(data: T, clock: C, threshold: u64) -> Self {
+        WatchdogLock {
+            lock: AtomicLock::new(data),
+            clock,
+            threshold,
+        }
+    }
+
+    /**
+    Locks the lock and accesses the data if available.
+    If the lock is unavailable, will return None.
+    */
+    #[track_caller]
+    pub fn lock(&self) -> Option<WatchdogGuard<'_, T, C>> {
+        let site = Location::caller();
+        self.lock.lock().map(|guard| WatchdogGuard {
+            guard: ManuallyDrop::new(guard),
+            lock: self,
+            site,
+            start: if WATCHING { self.clock.now() } else { 0 },
+        })
+    }
+
+    /**
+    The hold time, in ticks, at which the watchdog complains.
+    */
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+
+    /**
+    Consumes the lock, returning the inner data.
+    */
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T, C> core::fmt::Debug for WatchdogLock<T, C> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WatchdogLock")
+            .field("lock", &self.lock)
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+const WATCHING: bool = cfg!(any(debug_assertions, feature = "strict"));
+
+/**
+A guard for [WatchdogLock].
+
+Unlocks when dropped, then reports a [LongHold] if it was held too long.
+*/
+#[must_use]
+pub struct WatchdogGuard<'a, T, C: Clock> {
+    guard: ManuallyDrop<Guard<'a, T>>,
+    lock: &'a WatchdogLock<T, C>,
+    site: &'static Location<'static>,
+    start: u64,
+}
+
+impl<'a, T, C: Clock> Drop for WatchdogGuard<'a, T, C> {
+    fn drop(&mut self) {
+        let held = if WATCHING { self.lock.clock.now().wrapping_sub(self.start) } else { 0 };
+        //unlock first, so a panicking handler can't leave the lock held
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        if WATCHING && held >= self.lock.threshold {
+            long_hold(&LongHold { site: self.site, held, threshold: self.lock.threshold });
+        }
+    }
+}
+
+impl<'a, T, C: Clock> core::ops::Deref for WatchdogGuard<'a, T, C> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T, C: Clock> core::ops::DerefMut for WatchdogGuard<'a, T, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T, C: Clock> AsRef<T> for WatchdogGuard<'a, T, C> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T, C: Clock> AsMut<T> for WatchdogGuard<'a, T, C> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T, C: Clock> core::fmt::Debug for WatchdogGuard<'a, T, C> where T: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WatchdogGuard")
+            .field("data", &**self)
+            .field("site", &self.site)
+            .finish()
+    }
+}
+
+/**
+A guard that was held too long, reported by [WatchdogLock].
+
+Format it with `{}` for a message naming where the lock was taken.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct LongHold {
+    site: &'static Location<'static>,
+    held: u64,
+    threshold: u64,
+}
+
+impl LongHold {
+    /**
+    Where the lock was taken.
+    */
+    pub fn site(&self) -> &'static Location<'static> {
+        self.site
+    }
+
+    /**
+    How long the guard was held, in ticks of the lock's [Clock].
+    */
+    pub fn held(&self) -> u64 {
+        self.held
+    }
+
+    /**
+    The lock's threshold, in ticks.
+    */
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+}
+
+impl core::fmt::Display for LongHold {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "lock taken at {} was held for {} ticks (threshold {})", self.site, self.held, self.threshold)
+    }
+}
+
+static HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/**
+Installs `handler` to be called for every [LongHold], or goes back to panicking with None.
+
+Forward these to `log` or `tracing`, or use [warn_long_hold] to print them.
+The handler runs after the lock is released.
+*/
+pub fn set_long_hold_handler(handler: Option<fn(&LongHold)>) {
+    let ptr = handler.map_or(core::ptr::null_mut(), |handler| handler as *mut ());
+    HANDLER.store(ptr, Ordering::Release);
+}
+
+/**
+A long-hold handler that prints the [LongHold] to stderr.
+*/
+#[cfg(feature = "std")]
+pub fn warn_long_hold(hold: &LongHold) {
+    std::eprintln!("warning: {}", hold);
+}
+
+#[cold]
+fn long_hold(hold: &LongHold) {
+    let ptr = HANDLER.load(Ordering::Acquire);
+    if ptr.is_null() {
+        //panicking while already unwinding would abort, which loses the original panic
+        #[cfg(feature = "std")]
+        if std::thread::panicking() {
+            return;
+        }
+        panic!("{}", hold);
+    }
+    //Safety: only ever set from a fn(&LongHold)
+    let handler: fn(&LongHold) = unsafe { core::mem::transmute(ptr) };
+    handler(hold);
+}
@@ -0,0 +1,100 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Yield points, where fuzzers and schedulers can steer how threads interleave.
+
+With the `yield-points` feature, every [AtomicLock](crate::AtomicLock) calls the hook installed with
+[set_yield_hook] right before it tries to acquire or release the lock, including the locks inside
+other types in this crate.  A hook that yields, sleeps or blocks there decides which thread gets to
+go next, so a test can explore orderings that the OS scheduler would rarely produce.
+
+[seeded_yield] is a hook that perturbs the schedule from a seed, so a fuzzer can vary the
+interleaving with its input.  The OS still schedules the threads, so a seed makes a run likely,
+not certain, to repeat.
+
+Until a hook is installed, each yield point costs one atomic load.
+*/
+
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/**
+Where a yield point is.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum YieldPoint {
+    /**
+    About to try to acquire the lock.
+    */
+    Acquire,
+    /**
+    About to release the lock, from a guard, [AtomicLock::unlock](crate::AtomicLock::unlock) or the like.
+    */
+    Release,
+}
+
+/**
+A yield hook, given the yield point and the address of the lock.
+*/
+pub type YieldHook = fn(point: YieldPoint, lock: *const ());
+
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/**
+Installs `hook` at every yield point, or removes it with None.
+
+Hooks run while other locks may be held, so they must not take the lock they're told about.
+*/
+pub fn set_yield_hook(hook: Option<YieldHook>) {
+    let ptr = hook.map_or(core::ptr::null_mut(), |hook| hook as *mut ());
+    HOOK.store(ptr, Ordering::Release);
+}
+
+#[inline]
+pub(crate) fn yield_point(point: YieldPoint, lock: *const ()) {
+    let ptr = HOOK.load(Ordering::Acquire);
+    if !ptr.is_null() {
+        //Safety: only ever set from a YieldHook
+        let hook: YieldHook = unsafe { core::mem::transmute::<*mut (), YieldHook>(ptr) };
+        hook(point, lock);
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+static SEED: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+/**
+Seeds [seeded_yield].
+*/
+#[cfg(target_has_atomic = "64")]
+pub fn set_yield_seed(seed: u64) {
+    SEED.store(seed, Ordering::Relaxed);
+}
+
+/**
+A [YieldHook] that draws from the seed set with [set_yield_seed] to decide, at each yield point,
+whether to carry on, spin a while, or, with the `std` feature, yield the thread.
+
+Install it with `set_yield_hook(Some(seeded_yield))`.
+*/
+#[cfg(target_has_atomic = "64")]
+pub fn seeded_yield(_point: YieldPoint, _lock: *const ()) {
+    //splitmix64, so each yield point draws the next number in the seed's sequence
+    let mut z = SEED.fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    match z & 3 {
+        0 => {
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+            #[cfg(not(feature = "std"))]
+            for _ in 0..(z >> 56) {
+                core::hint::spin_loop();
+            }
+        }
+        1 => {
+            for _ in 0..(z >> 58) {
+                core::hint::spin_loop();
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,29 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Locking and unlocking bits of a BitLockPool. */
+
+use std::panic::catch_unwind;
+use atomiclock::BitLockPool;
+
+#[test]
+fn bit_pool() {
+    let pool = BitLockPool::<2>::new();
+    assert_eq!(pool.capacity(), 128);
+    let guard = pool.try_lock(70).unwrap();
+    assert_eq!(guard.index(), 70);
+    assert!(pool.is_locked(70));
+    assert!(pool.try_lock(70).is_none());
+    //neighbours in the same word are separate locks
+    assert!(!pool.is_locked(69));
+    let neighbour = pool.try_lock(71).unwrap();
+    drop(guard);
+    assert!(!pool.is_locked(70));
+    assert!(pool.is_locked(71));
+    drop(neighbour);
+    assert!(pool.try_lock(70).is_some());
+}
+
+#[test]
+fn bit_pool_out_of_bounds() {
+    let pool = BitLockPool::<1>::new();
+    assert!(catch_unwind(|| pool.try_lock(64).is_some()).is_err());
+}
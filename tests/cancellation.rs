@@ -0,0 +1,187 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Dropping async futures after they were woken, but before they were polled again, passes the wakeup on. */
+#![cfg(feature = "async")]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use atomiclock::{AsyncBarrier, AsyncRwLock, AsyncSemaphore, Notify};
+
+//counts how many times it was woken
+#[derive(Default)]
+struct Counter(AtomicUsize);
+
+impl Wake for Counter {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+struct Task {
+    counter: Arc<Counter>,
+    waker: Waker,
+}
+
+impl Task {
+    fn new() -> Self {
+        let counter = Arc::new(Counter::default());
+        Task { waker: Waker::from(counter.clone()), counter }
+    }
+
+    fn wakes(&self) -> usize {
+        self.counter.0.load(Ordering::SeqCst)
+    }
+
+    fn poll<F: Future + Unpin>(&self, future: &mut F) -> Poll<F::Output> {
+        Pin::new(future).poll(&mut Context::from_waker(&self.waker))
+    }
+}
+
+#[test]
+fn semaphore_acquire() {
+    let semaphore = AsyncSemaphore::new(1);
+    let permit = semaphore.try_acquire().unwrap();
+    let (a, b) = (Task::new(), Task::new());
+    let mut first = semaphore.acquire();
+    let mut second = semaphore.acquire();
+    assert!(a.poll(&mut first).is_pending());
+    assert!(b.poll(&mut second).is_pending());
+
+    drop(permit);
+    assert_eq!((a.wakes(), b.wakes()), (1, 0));
+    drop(first);
+    assert_eq!(b.wakes(), 1);
+    assert!(b.poll(&mut second).is_ready());
+}
+
+#[test]
+fn semaphore_acquire_not_woken() {
+    let semaphore = AsyncSemaphore::new(1);
+    let permit = semaphore.try_acquire().unwrap();
+    let (a, b) = (Task::new(), Task::new());
+    let mut first = semaphore.acquire();
+    let mut second = semaphore.acquire();
+    assert!(a.poll(&mut first).is_pending());
+    assert!(b.poll(&mut second).is_pending());
+
+    //a pending future leaves the queue, so the permit goes straight to the next one
+    drop(first);
+    drop(permit);
+    assert_eq!((a.wakes(), b.wakes()), (0, 1));
+    assert!(b.poll(&mut second).is_ready());
+}
+
+#[test]
+fn rwlock_write() {
+    let lock = AsyncRwLock::new(0);
+    let guard = lock.try_write().unwrap();
+    let (a, b) = (Task::new(), Task::new());
+    let mut first = lock.write();
+    let mut second = lock.write();
+    assert!(a.poll(&mut first).is_pending());
+    assert!(b.poll(&mut second).is_pending());
+
+    drop(guard);
+    assert_eq!((a.wakes(), b.wakes()), (1, 0));
+    drop(first);
+    assert_eq!(b.wakes(), 1);
+    match b.poll(&mut second) {
+        Poll::Ready(mut guard) => *guard = 1,
+        Poll::Pending => panic!("the second writer didn't get the lock"),
+    }
+    assert_eq!(*lock.try_read().unwrap(), 1);
+}
+
+#[test]
+fn rwlock_write_wakes_readers() {
+    let lock = AsyncRwLock::new(0);
+    let guard = lock.try_write().unwrap();
+    let (reader, writer) = (Task::new(), Task::new());
+    let mut read = lock.read();
+    let mut write = lock.write();
+    assert!(reader.poll(&mut read).is_pending());
+    assert!(writer.poll(&mut write).is_pending());
+
+    //writers are preferred, so only the writer is woken
+    drop(guard);
+    assert_eq!((reader.wakes(), writer.wakes()), (0, 1));
+    //and it was all that kept the reader out
+    drop(write);
+    assert_eq!(reader.wakes(), 1);
+    assert!(reader.poll(&mut read).is_ready());
+}
+
+#[test]
+fn rwlock_read() {
+    let lock = AsyncRwLock::new(0);
+    let guard = lock.try_write().unwrap();
+    let (a, b) = (Task::new(), Task::new());
+    let mut first = lock.read();
+    let mut second = lock.read();
+    assert!(a.poll(&mut first).is_pending());
+    assert!(b.poll(&mut second).is_pending());
+
+    drop(guard);
+    assert_eq!((a.wakes(), b.wakes()), (1, 1));
+    drop(first);
+    assert!(b.poll(&mut second).is_ready());
+}
+
+#[test]
+fn notified() {
+    let notify = Notify::new();
+    let (a, b) = (Task::new(), Task::new());
+    let mut first = notify.notified();
+    let mut second = notify.notified();
+    assert!(a.poll(&mut first).is_pending());
+    assert!(b.poll(&mut second).is_pending());
+
+    notify.notify_one();
+    assert_eq!((a.wakes(), b.wakes()), (1, 0));
+    drop(first);
+    assert_eq!(b.wakes(), 1);
+    assert!(b.poll(&mut second).is_ready());
+}
+
+#[test]
+fn notified_keeps_permit() {
+    let notify = Notify::new();
+    let a = Task::new();
+    let mut first = notify.notified();
+    assert!(a.poll(&mut first).is_pending());
+
+    notify.notify_one();
+    assert_eq!(a.wakes(), 1);
+    //with nobody else waiting, the notification becomes the permit
+    drop(first);
+    let b = Task::new();
+    assert!(b.poll(&mut notify.notified()).is_ready());
+}
+
+#[test]
+fn barrier_wait() {
+    let barrier = AsyncBarrier::new(3);
+    let (a, b, c) = (Task::new(), Task::new(), Task::new());
+    let mut first = barrier.wait();
+    let mut second = barrier.wait();
+    assert!(a.poll(&mut first).is_pending());
+    assert!(b.poll(&mut second).is_pending());
+
+    assert!(c.poll(&mut barrier.wait()).is_ready());
+    assert_eq!((a.wakes(), b.wakes()), (1, 1));
+    drop(first);
+    assert!(b.poll(&mut second).is_ready());
+}
+
+#[test]
+fn barrier_wait_keeps_arrival() {
+    let barrier = AsyncBarrier::new(2);
+    let (a, b) = (Task::new(), Task::new());
+    let mut first = barrier.wait();
+    assert!(a.poll(&mut first).is_pending());
+    //it arrived when polled, and dropping it doesn't take that back
+    drop(first);
+    assert!(b.poll(&mut barrier.wait()).is_ready());
+}
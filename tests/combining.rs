@@ -0,0 +1,36 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! A panicking operation in a CombiningLock. */
+#![cfg(feature = "alloc")]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use atomiclock::CombiningLock;
+
+#[test]
+fn combining_panic() {
+    struct SetOnDrop(Arc<AtomicBool>);
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let lock = CombiningLock::new(0);
+    let dropped = Arc::new(AtomicBool::new(false));
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let guard = lock.lock().unwrap();
+        assert!(!lock.apply_or_enqueue(|data| *data += 1));
+        assert!(!lock.apply_or_enqueue(|_| panic!("operation panicked")));
+        let after = SetOnDrop(dropped.clone());
+        assert!(!lock.apply_or_enqueue(move |data| {
+            let _after = &after;
+            *data += 10
+        }));
+        drop(guard);
+    }));
+    assert!(result.is_err());
+    //the operation after the panic was dropped without running, and the lock was unlocked
+    assert!(dropped.load(Ordering::SeqCst));
+    assert_eq!(*lock.lock().unwrap(), 1);
+}
@@ -0,0 +1,28 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Committing a CowLock whose value panics when cloned. */
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use atomiclock::CowLock;
+
+#[derive(Debug)]
+struct PanicOnClone(bool);
+
+impl Clone for PanicOnClone {
+    fn clone(&self) -> Self {
+        assert!(!self.0, "cloning panicked");
+        PanicOnClone(self.0)
+    }
+}
+
+#[test]
+fn commit_panic() {
+    let lock = CowLock::new(PanicOnClone(false));
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        lock.lock().unwrap().0 = true;
+    }));
+    assert!(result.is_err());
+    //the commit didn't happen, but the lock was unlocked
+    let mut guard = lock.lock().unwrap();
+    assert!(guard.0);
+    guard.0 = false;
+}
@@ -0,0 +1,28 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Fair handoff in HybridLock. */
+#![cfg(feature = "std")]
+
+use std::time::Duration;
+use atomiclock::{HybridGuard, HybridLock, HybridPolicy};
+
+#[test]
+fn hybrid_fair_handoff() {
+    //park right away, so the waiter is parked by the time we unlock
+    let lock = HybridLock::with_policy(0, HybridPolicy { spins: 0, yields: 0, adaptive: false, fair: false });
+    let mut guard = lock.lock();
+    *guard = 1;
+    std::thread::scope(|s| {
+        let waiter = s.spawn(|| {
+            let mut guard = lock.lock();
+            assert_eq!(*guard, 1);
+            *guard = 2;
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        HybridGuard::unlock_fair(guard);
+        //the lock went to the parked thread, so we can't barge in ahead of it
+        let guard = lock.lock();
+        assert_eq!(*guard, 2);
+        drop(guard);
+        waiter.join().unwrap();
+    });
+}
@@ -0,0 +1,30 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Notifications from Notify::notify_waiters. */
+#![cfg(feature = "async")]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use atomiclock::Notify;
+
+struct Noop;
+
+impl Wake for Noop {
+    fn wake(self: Arc<Self>) {}
+}
+
+fn poll<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    Pin::new(future).poll(&mut Context::from_waker(&Waker::from(Arc::new(Noop))))
+}
+
+#[test]
+fn notify_waiters_leaves_no_permit() {
+    let notify = Notify::new();
+    let mut notified = notify.notified();
+    assert!(poll(&mut notified).is_pending());
+    notify.notify_waiters();
+    assert!(poll(&mut notified).is_ready());
+    //the wakeup was for everyone waiting then, not one to pass on
+    assert!(poll(&mut notify.notified()).is_pending());
+}
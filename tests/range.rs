@@ -0,0 +1,36 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Overlap and slot limits of RangeLock. */
+
+use atomiclock::RangeLock;
+
+#[test]
+fn range_overlap() {
+    let mut data = [0u8; 10];
+    {
+        let lock: RangeLock<'_, u8, 2> = RangeLock::new(&mut data);
+        let mut low = lock.try_lock_range(0..5).unwrap();
+        assert!(lock.try_lock_range(3..7).is_none());
+        assert!(lock.try_lock_range(4..5).is_none());
+        let mut high = lock.try_lock_range(5..10).unwrap();
+        //empty ranges overlap nothing, and don't take a slot
+        assert!(lock.try_lock_range(3..3).is_some());
+        low.fill(1);
+        high.fill(2);
+        drop(low);
+        let middle = lock.try_lock_range(3..5).unwrap();
+        assert_eq!(*middle, [1, 1]);
+    }
+    assert_eq!(data, [1, 1, 1, 1, 1, 2, 2, 2, 2, 2]);
+}
+
+#[test]
+fn range_slots() {
+    let mut data = [0u8; 10];
+    let lock: RangeLock<'_, u8, 2> = RangeLock::new(&mut data);
+    let a = lock.try_lock_range(0..1).unwrap();
+    let _b = lock.try_lock_range(1..2).unwrap();
+    //disjoint, but every slot is taken
+    assert!(lock.try_lock_range(2..3).is_none());
+    drop(a);
+    assert!(lock.try_lock_range(2..3).is_some());
+}
@@ -0,0 +1,35 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Upgrading and downgrading AtomicRwLock guards. */
+
+use atomiclock::{AtomicRwLock, ReadGuard, WriteGuard};
+
+#[test]
+fn rwlock_upgrade() {
+    let lock = AtomicRwLock::new(1);
+    let read = lock.try_read().unwrap();
+    let other = lock.try_read().unwrap();
+    //not the only reader
+    let read = ReadGuard::try_upgrade(read).unwrap_err();
+    drop(other);
+    let mut write = ReadGuard::try_upgrade(read).unwrap();
+    assert!(lock.is_write_locked());
+    assert!(lock.try_read().is_none());
+    *write = 2;
+    drop(write);
+    assert_eq!(*lock.try_read().unwrap(), 2);
+}
+
+#[test]
+fn rwlock_downgrade() {
+    let lock = AtomicRwLock::new(1);
+    let mut write = lock.try_write().unwrap();
+    *write = 2;
+    let read = WriteGuard::downgrade(write);
+    assert!(!lock.is_write_locked());
+    //other readers get in, writers don't
+    assert_eq!(*lock.try_read().unwrap(), 2);
+    assert!(lock.try_write().is_none());
+    assert_eq!(*read, 2);
+    drop(read);
+    assert!(lock.try_write().is_some());
+}
@@ -0,0 +1,33 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Guards implement StableDeref. */
+#![cfg(all(feature = "stable_deref_trait", feature = "alloc"))]
+
+use std::rc::Rc;
+use std::sync::Arc;
+use stable_deref_trait::StableDeref;
+use atomiclock::AtomicLock;
+
+fn address<D: StableDeref<Target = u32>>(guard: &D) -> *const u32 {
+    &**guard
+}
+
+#[test]
+fn guards_keep_their_address() {
+    let lock = AtomicLock::new(1);
+    let guard = lock.lock().unwrap();
+    let before = address(&guard);
+    let moved = Box::new(guard);
+    assert_eq!(address(&*moved), before);
+    drop(moved);
+
+    let guard = Rc::new(AtomicLock::new(2)).lock_rc().unwrap();
+    let before = address(&guard);
+    let moved = vec![guard];
+    assert_eq!(address(&moved[0]), before);
+    drop(moved);
+
+    let guard = Arc::new(AtomicLock::new(3)).lock_arc().unwrap();
+    let before = address(&guard);
+    let moved = Some(guard);
+    assert_eq!(address(moved.as_ref().unwrap()), before);
+}
@@ -0,0 +1,26 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Awaiting an AtomicLock within tokio's cooperative budget. */
+#![cfg(feature = "tokio")]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use atomiclock::AtomicLock;
+
+#[test]
+fn lock_yields_when_out_of_budget() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        let ran = Arc::new(AtomicBool::new(false));
+        let other = ran.clone();
+        tokio::spawn(async move { other.store(true, Ordering::SeqCst) });
+        let lock = AtomicLock::new(0);
+        //the lock is always free, so only the budget makes this yield to the other task
+        for _ in 0..10_000 {
+            *(&lock).await += 1;
+            if ran.load(Ordering::SeqCst) {
+                return;
+            }
+        }
+        panic!("never yielded to the other task");
+    });
+}
@@ -0,0 +1,41 @@
+//SPDX-License-Identifier: MIT OR Apache-2.0
+/*! Counting with TryWaitGroup. */
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use atomiclock::TryWaitGroup;
+
+#[test]
+fn wait_group() {
+    let group = TryWaitGroup::new();
+    assert!(group.is_done());
+    group.add(2);
+    assert_eq!(group.try_wait(), Err(2));
+    assert!(!group.done());
+    assert_eq!(group.try_wait(), Err(1));
+    assert!(group.done());
+    assert_eq!(group.try_wait(), Ok(()));
+}
+
+#[test]
+fn wait_group_threads() {
+    let group = TryWaitGroup::new();
+    group.add(4);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| group.done());
+        }
+    });
+    assert!(group.is_done());
+}
+
+#[test]
+fn wait_group_misuse() {
+    let group = TryWaitGroup::new();
+    //too many done leaves the count at 0
+    assert!(catch_unwind(AssertUnwindSafe(|| group.done())).is_err());
+    assert_eq!(group.try_wait(), Ok(()));
+    //overflow leaves the count as it was
+    group.add(usize::MAX);
+    assert!(catch_unwind(AssertUnwindSafe(|| group.add(1))).is_err());
+    assert_eq!(group.try_wait(), Err(usize::MAX));
+}